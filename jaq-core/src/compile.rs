@@ -3,7 +3,8 @@
 use crate::load::{self, lex, parse};
 use crate::{ops, Bind as Arg};
 use alloc::collections::{BTreeMap, BTreeSet};
-use alloc::{boxed::Box, string::String, vec::Vec};
+use alloc::{boxed::Box, format, string::String, vec::Vec};
+use core::ops::Range;
 
 type NativeId = usize;
 type ModId = usize;
@@ -12,6 +13,32 @@ type VarSkip = usize;
 type Arity = usize;
 
 /// Function from a value to a stream of value results.
+///
+/// A filter has to be compiled only once and can then be run on any number of inputs.
+/// Running a filter does not parse or compile anything anew;
+/// it only traverses the program graph stored in the filter.
+///
+/// ~~~
+/// use jaq_core::{data, load, unwrap_valr, Compiler, Ctx, Vars};
+/// use jaq_json::Val;
+///
+/// let program = load::File { code: ".[0] + 1", path: () };
+/// let loader = load::Loader::new(jaq_core::defs());
+/// let arena = load::Arena::default();
+/// let modules = loader.load(&arena, program).unwrap();
+/// let filter = Compiler::default()
+///     .with_funs(jaq_core::funs())
+///     .compile(modules)
+///     .unwrap();
+///
+/// // the context can be reused as well
+/// let ctx = Ctx::<data::JustLut<Val>>::new(&filter.lut, Vars::new([]));
+/// for i in 0..1000_isize {
+///     let input = Val::from_iter([Val::from(i)]);
+///     let mut out = filter.id.run((ctx.clone(), input)).map(unwrap_valr);
+///     assert_eq!(out.next(), Some(Ok(Val::from(i + 1))));
+/// }
+/// ~~~
 #[derive(Debug, Clone)]
 pub struct Filter<F> {
     /// Program graph, implemented as lookup table
@@ -108,6 +135,8 @@ pub(crate) enum Term<T = TermId> {
     CallDef(TermId, Box<[Arg<T>]>, VarSkip, CallType),
 
     Native(NativeId, Box<[Arg<T>]>),
+    /// Call to a filter outside the main module, whose errors get the given span
+    At(T, Range<usize>),
 
     /// Binding of a break label (`label $x | f`)
     Label(T),
@@ -224,6 +253,13 @@ pub struct Compiler<S, F> {
 
     locals: Locals<S>,
 
+    /// code and file name of the module currently compiled, used for `$__loc__`
+    loc: Option<(S, &'static str)>,
+    /// true if the module currently compiled is the main module
+    in_main: bool,
+    /// true if errors should record spans, see [`Self::with_spans`]
+    spans: bool,
+
     errs: Vec<Error<S>>,
 }
 
@@ -242,6 +278,9 @@ impl<S, F> Default for Compiler<S, F> {
             global_vars: Vec::new(),
             imported_vars: Vec::new(),
             locals: Locals::default(),
+            loc: None,
+            in_main: false,
+            spans: false,
             errs: Vec::new(),
         }
     }
@@ -498,6 +537,15 @@ impl<'s, F> Compiler<&'s str, F> {
         }
     }
 
+    /// Record in errors the span of the call in the main module that yielded them.
+    ///
+    /// This is disabled by default, because it slows down
+    /// calls in the main module to filters defined outside of it.
+    /// See [`crate::Error::span`] for details.
+    pub fn with_spans(self, spans: bool) -> Self {
+        Self { spans, ..self }
+    }
+
     /// Compile the given modules.
     pub fn compile<P>(
         mut self,
@@ -511,12 +559,15 @@ impl<'s, F> Compiler<&'s str, F> {
 
         let mut errs = Vec::new();
         for (file, module) in mods.deps {
+            self.loc = Some((file.code, "<module>"));
             let defs = self.open_module(module);
             let defs = self.module(defs);
             self.mod_map.push(defs);
             self.close_module(file, &mut errs)
         }
         let (file, module) = mods.main;
+        self.loc = Some((file.code, "<top-level>"));
+        self.in_main = true;
         let main = self.open_module(module);
         let id = self.iterm(main);
         self.close_module(file, &mut errs);
@@ -813,7 +864,7 @@ impl<'s, F> Compiler<&'s str, F> {
             None => return self.fail(module, Undefined::Mod),
         };
         if let Some(call) = self.call_mod_id(*mid, name, args) {
-            return call;
+            return self.at(name, call);
         }
         self.fail(name, Undefined::Filter(args.len()))
     }
@@ -825,18 +876,50 @@ impl<'s, F> Compiler<&'s str, F> {
         }
         for mid in self.included_mods.iter().rev() {
             if let Some(call) = self.call_mod_id(*mid, name, args) {
-                return (call, Tr::new());
+                return (self.at(name, call), Tr::new());
             }
         }
         for (nid, (sig, _f)) in self.lut.funs.iter().enumerate() {
             if sig.matches(name, args) {
-                return (Term::Native(nid, binds(&sig.args, args)), Tr::new());
+                let call = Term::Native(nid, binds(&sig.args, args));
+                return (self.at(name, call), Tr::new());
             }
         }
+        if name == "builtins" && args.is_empty() {
+            return (self.builtins(), Tr::new());
+        }
 
         (self.fail(name, Undefined::Filter(args.len())), Tr::new())
     }
 
+    /// Construct an array with the names and arities of all native filters and
+    /// all definitions in the prelude, such as `"map/1"`.
+    fn builtins(&mut self) -> Term {
+        let natives = self.lut.funs.iter().map(|(sig, _f)| sig);
+        let defs = self
+            .mod_map
+            .first()
+            .into_iter()
+            .flatten()
+            .map(|(sig, ..)| sig);
+        // omit internal helpers, such as `!empty`, `splits_`, or `min_or_empty`
+        let internal =
+            |name: &str| name.starts_with('!') || name.ends_with('_') || name.ends_with("_empty");
+        let sigs = natives.chain(defs).filter(|sig| !internal(sig.name));
+        let mut names: BTreeSet<_> = sigs
+            .map(|sig| format!("{}/{}", sig.name, sig.args.len()))
+            .collect();
+        names.insert("builtins/0".into());
+        let mut names = names.into_iter().rev().map(Term::Str);
+        let t = match names.next() {
+            Some(last) => names.fold(last, |acc, x| {
+                Term::Comma(self.lut.insert_term(x), self.lut.insert_term(acc))
+            }),
+            None => self.call("!empty", &[], &Tr::new()).0,
+        };
+        Term::Arr(self.lut.insert_term(t))
+    }
+
     fn var(&mut self, x: &'s str) -> Term {
         let mut i = self.locals.vars.total;
 
@@ -857,9 +940,50 @@ impl<'s, F> Compiler<&'s str, F> {
                 i += 1;
             }
         }
+        if x == "$__loc__" {
+            if let Some(loc) = self.loc_obj(x) {
+                return loc;
+            }
+        }
         self.fail(x, Undefined::Var)
     }
 
+    /// Construct an object `{file: ..., line: ...}` that contains the location of `x`.
+    ///
+    /// This returns `None` if `x` is not part of the code of the current module,
+    /// such as for the prelude.
+    fn loc_obj(&mut self, x: &'s str) -> Option<Term> {
+        let (code, file) = self.loc?;
+        let start = (x.as_ptr() as usize).checked_sub(code.as_ptr() as usize);
+        let line = code.get(..start?)?.matches('\n').count() + 1;
+        let mut kv = |k: &str, v| {
+            let k = self.lut.insert_term(Term::Str(k.into()));
+            Term::ObjSingle(k, self.lut.insert_term(v))
+        };
+        let file = kv("file", Term::Str(file.into()));
+        let line = kv("line", Term::Int(line as isize));
+        Some(self.sum_or(|| Term::ObjEmpty, Vec::from([file, line])))
+    }
+
+    /// Attribute errors of the call `t` to the filter name `x`.
+    ///
+    /// This is only done if spans are enabled and
+    /// `x` is part of the code of the main module,
+    /// because a bare byte range cannot tell which module it refers to.
+    /// That way, calls inside the standard library do not incur any overhead.
+    fn at(&mut self, x: &'s str, t: Term) -> Term {
+        let span = self.loc.filter(|_| self.spans && self.in_main);
+        let span = span.and_then(|(code, _file)| {
+            let start = (x.as_ptr() as usize).checked_sub(code.as_ptr() as usize)?;
+            let end = start.checked_add(x.len())?;
+            (end <= code.len()).then_some(start..end)
+        });
+        match span {
+            Some(span) => Term::At(self.lut.insert_term(t), span),
+            None => t,
+        }
+    }
+
     fn break_(&mut self, x: &'s str) -> Term {
         if let Some(l) = self.locals.vars.bound.get_last(&Bind::Label(x)) {
             return Term::Var(self.locals.vars.total - l);
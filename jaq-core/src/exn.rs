@@ -1,8 +1,9 @@
 //! Exceptions and errors.
 
 use crate::{compile::TermId, filter::Vars, RcList};
-use alloc::{boxed::Box, string::String, string::ToString, vec::Vec};
+use alloc::{borrow::Cow, boxed::Box, string::String, string::ToString, vec::Vec};
 use core::fmt::{self, Display};
+use core::ops::Range;
 
 /// Exception.
 ///
@@ -47,6 +48,14 @@ impl<V> CallInput<V> {
 }
 
 impl<V> Exn<'_, V> {
+    /// If the exception is an error without a span, set its span.
+    pub(crate) fn at(mut self, span: &Range<usize>) -> Self {
+        if let Inner::Err(e) = &mut self.0 {
+            e.1.get_or_insert_with(|| span.clone());
+        }
+        self
+    }
+
     /// If the exception is an error, yield it, else yield the exception.
     pub(crate) fn get_err(self) -> Result<Error<V>, Self> {
         match self.0 {
@@ -63,46 +72,76 @@ impl<V> From<Error<V>> for Exn<'_, V> {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
-enum Part<V, S = &'static str> {
+enum Part<V, S = Cow<'static, str>> {
     Val(V),
     Str(S),
 }
 
 /// Error that occurred during filter execution.
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub struct Error<V>(Part<V, Vec<Part<V>>>);
+///
+/// Two errors are equal if their messages are equal, regardless of their spans.
+#[derive(Clone, Debug)]
+pub struct Error<V>(Part<V, Vec<Part<V>>>, Option<Range<usize>>);
+
+impl<V: PartialEq> PartialEq for Error<V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<V: Eq> Eq for Error<V> {}
 
 impl<V> Error<V> {
     /// Create a new error from a value.
     pub fn new(v: V) -> Self {
-        Self(Part::Val(v))
+        Self(Part::Val(v), None)
+    }
+
+    /// Return the byte range in the main module of the filter that raised the error.
+    ///
+    /// This is the name of the innermost call that yielded the error,
+    /// among all calls in the main module to filters that are not defined in the main module,
+    /// such as `error`, `tostring`, or `ltrimstr`.
+    /// Errors that are not yielded by such a call, such as from `1 + "a"`,
+    /// have no span.
+    /// Spans are only recorded if the filter was compiled with
+    /// [`crate::Compiler::with_spans`].
+    pub fn span(&self) -> Option<&Range<usize>> {
+        self.1.as_ref()
     }
 
     /// Create a path expression error.
     pub fn path_expr(v: V) -> Self {
-        Self(Part::Str(Vec::from([
-            Part::Str("invalid path expression with input "),
+        [
+            Part::Str("invalid path expression with input ".into()),
             Part::Val(v),
-        ])))
+        ]
+        .into_iter()
+        .collect()
     }
 
     /// Create a type error.
     pub fn typ(v: V, typ: &'static str) -> Self {
         use Part::{Str, Val};
-        [Str("cannot use "), Val(v), Str(" as "), Str(typ)]
-            .into_iter()
-            .collect()
+        [
+            Str("cannot use ".into()),
+            Val(v),
+            Str(" as ".into()),
+            Str(typ.into()),
+        ]
+        .into_iter()
+        .collect()
     }
 
     /// Create a math error.
     pub fn math(l: V, op: crate::ops::Math, r: V) -> Self {
         use Part::{Str, Val};
         [
-            Str("cannot calculate "),
+            Str("cannot calculate ".into()),
             Val(l),
-            Str(" "),
-            Str(op.as_str()),
-            Str(" "),
+            Str(" ".into()),
+            Str(op.as_str().into()),
+            Str(" ".into()),
             Val(r),
         ]
         .into_iter()
@@ -112,7 +151,28 @@ impl<V> Error<V> {
     /// Create an indexing error.
     pub fn index(l: V, r: V) -> Self {
         use Part::{Str, Val};
-        [Str("cannot index "), Val(l), Str(" with "), Val(r)]
+        [
+            Str("cannot index ".into()),
+            Val(l),
+            Str(" with ".into()),
+            Val(r),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    /// Create an error with a message that shows a value, such as
+    /// `cannot parse "abc" as number`.
+    ///
+    /// The message consists of `pre`, followed by `v`, followed by `post`.
+    /// Like in the other errors above, long values are cut off.
+    pub fn with_val(
+        pre: impl Into<Cow<'static, str>>,
+        v: V,
+        post: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        use Part::{Str, Val};
+        [Str(pre.into()), Val(v), Str(post.into())]
             .into_iter()
             .collect()
     }
@@ -121,13 +181,13 @@ impl<V> Error<V> {
 impl<V: From<String>> Error<V> {
     /// Build an error from something that can be converted to a string.
     pub fn str(s: impl ToString) -> Self {
-        Self(Part::Val(V::from(s.to_string())))
+        Self::new(V::from(s.to_string()))
     }
 }
 
 impl<V> FromIterator<Part<V>> for Error<V> {
     fn from_iter<T: IntoIterator<Item = Part<V>>>(iter: T) -> Self {
-        Self(Part::Str(iter.into_iter().collect()))
+        Self(Part::Str(iter.into_iter().collect()), None)
     }
 }
 
@@ -142,12 +202,60 @@ impl<V: From<String> + Display> Error<V> {
     }
 }
 
+/// Maximal number of characters of a value shown in an error message.
+///
+/// Values that are longer are cut off and followed by `...`.
+/// This applies to the values shown by errors such as [`Error::typ`] or [`Error::with_val`].
+/// It does not apply to errors created by [`Error::new`], such as by `error($v)`,
+/// because their payload is shown as-is.
+const PREVIEW_LEN: usize = 30;
+
+/// Writer that fails after having written a given number of characters.
+struct Truncate<'a, 'b> {
+    f: &'a mut fmt::Formatter<'b>,
+    left: usize,
+    cut: bool,
+}
+
+impl fmt::Write for Truncate<'_, '_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        match s.char_indices().nth(self.left) {
+            Some((i, _)) => {
+                self.cut = true;
+                self.f.write_str(&s[..i])?;
+                Err(fmt::Error)
+            }
+            None => {
+                self.left -= s.chars().count();
+                self.f.write_str(s)
+            }
+        }
+    }
+}
+
+/// Display at most [`PREVIEW_LEN`] characters of a value.
+///
+/// This stops formatting the value as soon as the limit is reached,
+/// so it is cheap even for huge values.
+fn preview(v: &impl Display, f: &mut fmt::Formatter) -> fmt::Result {
+    use fmt::Write;
+    let mut t = Truncate {
+        f,
+        left: PREVIEW_LEN,
+        cut: false,
+    };
+    match write!(t, "{v}") {
+        Err(_) if t.cut => f.write_str("..."),
+        r => r,
+    }
+}
+
 impl<V: Display> Display for Error<V> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match &self.0 {
             Part::Val(v) => v.fmt(f),
             Part::Str(parts) => parts.iter().try_for_each(|part| match part {
-                Part::Val(v) => v.fmt(f),
+                Part::Val(v) => preview(v, f),
                 Part::Str(s) => s.fmt(f),
             }),
         }
@@ -7,6 +7,7 @@ use crate::fold::fold;
 use crate::val::{ValR, ValT, ValX, ValXs};
 use crate::{exn, rc_lazy_list, Bind as Arg, Error, Exn, RcList};
 use alloc::boxed::Box;
+use core::ops::Range;
 use dyn_clone::DynClone;
 
 /// Combination of context and input value.
@@ -56,6 +57,8 @@ pub struct Ctx<'a, D: DataT + ?Sized> {
     ///
     /// This is used to create fresh break IDs.
     labels: usize,
+    /// Number of calls to defined filters that may still be nested
+    depth: usize,
 }
 
 impl<'a, D: DataT> Clone for Ctx<'a, D> {
@@ -75,9 +78,29 @@ impl<'a, D: DataT> Ctx<'a, D> {
             data,
             vars,
             labels: 0,
+            depth: usize::MAX,
         }
     }
 
+    /// Limit the nesting depth of calls to defined filters.
+    ///
+    /// When the evaluation of a filter nests more than `depth` calls,
+    /// it yields the error "recursion limit exceeded".
+    /// This can be used to prevent stack overflows when running untrusted filters.
+    /// Tail calls do not count towards this limit, because they do not grow the stack.
+    ///
+    /// By default, the depth is unlimited.
+    pub fn with_depth_limit(self, depth: usize) -> Self {
+        Self { depth, ..self }
+    }
+
+    /// Enter a call to a defined filter, failing if the depth limit is exceeded.
+    fn deeper(mut self) -> Result<Self, Exn<'a, D::V<'a>>> {
+        let fail = || Exn::from(Error::str("recursion limit exceeded"));
+        self.depth = self.depth.checked_sub(1).ok_or_else(fail)?;
+        Ok(self)
+    }
+
     /// Add a new variable binding.
     fn cons_var(mut self, x: D::V<'a>) -> Self {
         self.vars.0 = self.vars.0.cons(Bind::Var(x));
@@ -110,6 +133,7 @@ impl<'a, D: DataT> Ctx<'a, D> {
             vars,
             data: self.data.clone(),
             labels: self.labels,
+            depth: self.depth,
         }
     }
 
@@ -382,6 +406,11 @@ where
     Box::new(fold(xs, init, f, |_| (), |_, _| None, Some))
 }
 
+/// Give errors that do not yet have a span the given span.
+fn at<'a, T: 'a, V: 'a>(ys: ValXs<'a, T, V>, span: &'a Range<usize>) -> ValXs<'a, T, V> {
+    Box::new(ys.map(move |y| y.map_err(|e| e.at(span))))
+}
+
 fn lazy<I: Iterator, F: FnOnce() -> I>(f: F) -> impl Iterator<Item = I::Item> {
     core::iter::once_with(f).flatten()
 }
@@ -539,7 +568,7 @@ impl Id {
             }),
             Ast::Math(l, op, r) => Box::new(cartesian(l, r, cv).map(|(x, y)| Ok(op.run(x?, y?)?))),
             Ast::Cmp(l, op, r) => {
-                Box::new(cartesian(l, r, cv).map(|(x, y)| Ok(D::V::from(op.run(&x?, &y?)))))
+                Box::new(cartesian(l, r, cv).map(|(x, y)| Ok(D::V::from(x?.cmp_op(*op, &y?)))))
             }
 
             Ast::Fold(xs, pat, init, update, fold_type) => {
@@ -552,13 +581,19 @@ impl Id {
                 Bind::Label(l) => box_once(Err(Exn(exn::Inner::Break(*l)))),
             },
             Ast::CallDef(id, args, skip, call_typ) => {
+                let ctx = match cv.0.clone().skip_vars(*skip).deeper() {
+                    Ok(ctx) => ctx,
+                    Err(e) => return box_once(Err(e)),
+                };
                 let data = cv.0.data.clone();
+                let depth = ctx.depth;
                 let with_vars = move |vars| Ctx {
                     vars,
                     data: data.clone(),
                     labels: cv.0.labels,
+                    depth,
                 };
-                let cvs = bind_vars(args, cv.0.clone().skip_vars(*skip), cv, Clone::clone);
+                let cvs = bind_vars(args, ctx, cv, Clone::clone);
                 let (into, from) = (exn::CallInput::Run, exn::CallInput::unwrap_run);
                 def_run(id, call_typ, cvs, Id::run, with_vars, into, from)
             }
@@ -566,6 +601,7 @@ impl Id {
                 let cvs = bind_vars(args, cv.0.with_vars(Vars::new([])), cv, Clone::clone);
                 flat_map_then(cvs, |cv| (cv.0.lut().funs[*id].run)(cv))
             }
+            Ast::At(f, span) => at(f.run(cv), span),
             Ast::Label(id) => label_run(cv, |cv| id.run(cv)),
         }
     }
@@ -638,13 +674,19 @@ impl Id {
                 fold_run(xs, cv, init, update, fold_type, |f, cv| f.paths(cv))
             }
             Ast::CallDef(id, args, skip, call_typ) => {
+                let ctx = match cv.0.clone().skip_vars(*skip).deeper() {
+                    Ok(ctx) => ctx,
+                    Err(e) => return box_once(Err(e)),
+                };
                 let data = cv.0.data.clone();
+                let depth = ctx.depth;
                 let with_vars = move |vars| Ctx {
                     vars,
                     data: data.clone(),
                     labels: cv.0.labels,
+                    depth,
                 };
-                let cvs = bind_vars(args, cv.0.clone().skip_vars(*skip), cv, proj_val);
+                let cvs = bind_vars(args, ctx, cv, proj_val);
                 let (into, from) = (exn::CallInput::Paths, exn::CallInput::unwrap_paths);
                 def_run(id, call_typ, cvs, Id::paths, with_vars, into, from)
             }
@@ -653,6 +695,7 @@ impl Id {
                 let cvs = bind_vars(args, cv.0.with_vars(Vars::new([])), cv, proj_val);
                 flat_map_then(cvs, |cv| (cv.0.lut().funs[*id].paths)(cv))
             }
+            Ast::At(f, span) => at(f.paths(cv), span),
         }
     }
 
@@ -720,7 +763,11 @@ impl Id {
             },
             Ast::CallDef(id, args, skip, _call_typ) => {
                 let init = cv.1.clone();
-                let cvs = bind_vars(args, cv.0.clone().skip_vars(*skip), cv, Clone::clone);
+                let ctx = match cv.0.clone().skip_vars(*skip).deeper() {
+                    Ok(ctx) => ctx,
+                    Err(e) => return box_once(Err(e)),
+                };
+                let cvs = bind_vars(args, ctx, cv, Clone::clone);
                 reduce(cvs, init, move |cv, v| id.update((cv.0, v), f.clone()))
             }
             Ast::Native(id, args) => {
@@ -730,6 +777,7 @@ impl Id {
                     (cv.0.lut().funs[*id].update)((cv.0, v), f.clone())
                 })
             }
+            Ast::At(g, span) => at(g.update(cv, f), span),
         }
     }
 }
@@ -1,7 +1,7 @@
 use crate::box_iter::BoxIter;
 use crate::native::{bome, v, Filter, RunPathsPtr};
 use crate::{Bind, DataT, Error, Exn, RunPtr, ValT, ValX};
-use alloc::{boxed::Box, vec::Vec};
+use alloc::{boxed::Box, collections::VecDeque, vec::Vec};
 
 pub fn run<D: DataT>() -> Box<[Filter<RunPtr<D>>]>
 where
@@ -91,6 +91,25 @@ macro_rules! last {
     };
 }
 
+/// Return the last `n` outputs of `iter`, or its first error.
+///
+/// This uses memory proportional to `n`, not to the number of outputs.
+fn take_last<'a, T, V: ValT>(
+    iter: impl Iterator<Item = ValX<'a, T, V>>,
+    mut n: V,
+) -> ValX<'a, VecDeque<T>, V> {
+    let mut buf = VecDeque::new();
+    for x in iter {
+        buf.push_back(x?);
+        if n > 0.into() {
+            n = (n - 1.into())?;
+        } else {
+            buf.pop_front();
+        }
+    }
+    Ok(buf)
+}
+
 macro_rules! while_gtz {
     ( $n:expr, $gtz:expr, $lez:expr ) => {{
         let mut n = Some($n);
@@ -110,7 +129,7 @@ macro_rules! while_gtz {
 /// In principle, we could implement the following filters as follows:
 ///
 /// ~~~ text
-/// def limit($n; f): if $n <= 0 then empty else label $out |
+/// def limit($n; f): if $n < 0 then [f][$n:][] elif $n == 0 then empty else label $out |
 ///   foreach f as $x ($n; . - 1; if . <= 0 then $x, break $out else $x end) end;
 /// def skip($n; f): if $n <= 0 then f else
 ///   foreach f as $x ($n; . - 1; if . >= 0 then empty else $x end) end;
@@ -123,7 +142,14 @@ macro_rules! limit {
     ( $run:ident ) => {
         |mut cv| {
             let ((f, fc), n) = (cv.0.pop_fun(), cv.0.pop_var());
-            if n <= 0.into() {
+            if n < 0.into() {
+                let iter = f.$run((fc, cv.1));
+                return match (-n).map_err(Exn::from).and_then(|n| take_last(iter, n)) {
+                    Ok(last) => Box::new(last.into_iter().map(Ok)),
+                    Err(e) => Box::new(core::iter::once(Err(e))),
+                };
+            }
+            if n == 0.into() {
                 return Box::new(core::iter::empty());
             }
             let mut iter = f.$run((fc, cv.1));
@@ -150,6 +176,27 @@ macro_rules! skip {
     };
 }
 
+macro_rules! nth {
+    ( $run:ident ) => {
+        |mut cv| {
+            let ((f, fc), n) = (cv.0.pop_fun(), cv.0.pop_var());
+            if n < 0.into() {
+                let e = Error::str("Out of bounds negative array index");
+                return Box::new(core::iter::once(Err(e.into())));
+            }
+            let mut iter = f.$run((fc, cv.1));
+            let rest = while_gtz!(
+                n,
+                if let Some(e) = iter.next()?.err() {
+                    return Some(Err(e));
+                },
+                iter.next()
+            );
+            Box::new(rest.take(1))
+        }
+    };
+}
+
 pub fn paths<D: DataT>() -> Box<[Filter<RunPathsPtr<D>>]>
 where
     for<'a> D::V<'a>: ValT,
@@ -161,5 +208,6 @@ where
         ("last", f(), (last!(run), last!(paths))),
         ("limit", vf(), (limit!(run), limit!(paths))),
         ("skip", vf(), (skip!(run), skip!(paths))),
+        ("nth", vf(), (nth!(run), nth!(paths))),
     ])
 }
@@ -5,6 +5,7 @@
 //! The example below demonstrates how to use this crate.
 //! See the implementation in the `jaq` crate if you are interested in
 //! more complex use cases, such as lazy JSON file loading, error handling etc.
+//! See the [`native`] module if you want to define your own native filters.
 //!
 //! ~~~
 //! use jaq_core::{data, unwrap_valr, Compiler, Ctx, Vars};
@@ -73,6 +74,9 @@ pub use val::{unwrap_valr, ValR, ValT, ValX, ValXs};
 use rc_list::List as RcList;
 use stack::Stack;
 
+#[cfg(feature = "par")]
+use alloc::vec::Vec;
+
 /// Argument of a definition, such as `$v` or `f` in `def foo($v; f): ...`.
 ///
 /// In jq, we can bind filters in three different ways:
@@ -132,6 +136,58 @@ impl<V: ValT + 'static> Filter<data::JustLut<V>> {
     }
 }
 
+#[cfg(feature = "par")]
+impl<V: ValT + Send + Sync + 'static> Filter<data::JustLut<V>> {
+    /// Run a filter on given inputs in parallel, using up to `threads` threads.
+    ///
+    /// This takes up to `batch` inputs at a time from `inputs` and
+    /// splits them into contiguous chunks, one per thread.
+    /// Once all threads have finished, the outputs of the batch are yielded and
+    /// the next batch is taken, so that at most one batch is held in memory.
+    /// The outputs are yielded in the same order as if
+    /// the filter had been run on the inputs sequentially.
+    /// If the filter panics in a thread, the panic is propagated to the caller.
+    ///
+    /// Because filters with [`data::JustLut`] cannot access shared data
+    /// (such as the input stream for `input`/`inputs`),
+    /// running them on different inputs in parallel is safe.
+    ///
+    /// This is only available with the `par` feature.
+    pub fn par_run<'a>(
+        &'a self,
+        inputs: impl IntoIterator<Item = V> + 'a,
+        threads: usize,
+        batch: usize,
+    ) -> impl Iterator<Item = ValR<V>> + 'a {
+        let (threads, batch) = (threads.max(1), batch.max(1));
+        let mut inputs = inputs.into_iter();
+        let batches = core::iter::from_fn(move || {
+            let xs: Vec<V> = inputs.by_ref().take(batch).collect();
+            (!xs.is_empty()).then(|| self.par_run_batch(&xs, threads))
+        });
+        batches.flatten()
+    }
+
+    fn par_run_batch(&self, inputs: &[V], threads: usize) -> Vec<ValR<V>> {
+        let chunk_size = (inputs.len() + threads - 1) / threads;
+        let run = |xs: &[V]| -> Vec<ValR<V>> {
+            let ctx = Ctx::<data::JustLut<V>>::new(&self.lut, Vars::new([]));
+            let outs = xs
+                .iter()
+                .flat_map(|x| self.id.run((ctx.clone(), x.clone())));
+            outs.map(unwrap_valr).collect()
+        };
+        std::thread::scope(|s| {
+            let chunks = inputs.chunks(chunk_size);
+            let handles: Vec<_> = chunks.map(|xs| s.spawn(move || run(xs))).collect();
+            let join = |h: std::thread::ScopedJoinHandle<_>| {
+                h.join().unwrap_or_else(|e| std::panic::resume_unwind(e))
+            };
+            handles.into_iter().flat_map(join).collect()
+        })
+    }
+}
+
 /// Minimal set of definitions.
 ///
 /// This depends on [`funs()`] being loaded.
@@ -1,4 +1,74 @@
 //! Native filter construction tools.
+//!
+//! Native filters are filters implemented in Rust instead of jq.
+//! A native filter is given by a [`Filter`], namely a tuple consisting of
+//! its name, its arguments (usually created with [`v`]), and its implementation.
+//! The implementation is a function pointer that receives
+//! the execution context and the input value and
+//! returns an iterator over output values.
+//!
+//! To make your own native filters available to jq programs,
+//! convert them with [`run`] and chain them to the filters provided by
+//! [`crate::funs`] & co. before passing them to [`crate::Compiler::with_funs`].
+//!
+//! Because implementations are function pointers, they cannot capture state.
+//! Instead, state can be passed via the global data of a custom [`DataT`],
+//! which native filters access via [`crate::Ctx::data`].
+//! The following example shows a filter `tick` that
+//! yields how often it was called so far during the current run:
+//!
+//! ~~~
+//! use core::cell::Cell;
+//! use jaq_core::{data, unwrap_valr, Compiler, Ctx, DataT, Lut, Vars};
+//! use jaq_core::load::{Arena, File, Loader};
+//! use jaq_core::native::{bome, run, v, Fun};
+//! use jaq_json::Val;
+//!
+//! struct DataKind;
+//!
+//! impl DataT for DataKind {
+//!     type V<'a> = Val;
+//!     type Data<'a> = &'a Data<'a>;
+//! }
+//!
+//! struct Data<'a> {
+//!     lut: &'a Lut<DataKind>,
+//!     ticks: Cell<usize>,
+//! }
+//!
+//! impl<'a> data::HasLut<'a, DataKind> for &'a Data<'a> {
+//!     fn lut(&self) -> &'a Lut<DataKind> {
+//!         self.lut
+//!     }
+//! }
+//!
+//! let tick: Fun<DataKind> = run(("tick", v(0), |cv| {
+//!     let ticks = &cv.0.data().ticks;
+//!     ticks.set(ticks.get() + 1);
+//!     bome(Ok(Val::from(ticks.get())))
+//! }));
+//!
+//! let defs = jaq_core::defs().chain(jaq_std::defs()).chain(jaq_json::defs());
+//! let funs = jaq_core::funs().chain(jaq_std::funs()).chain(jaq_json::funs());
+//!
+//! let loader = Loader::new(defs);
+//! let arena = Arena::default();
+//! let program = File { code: "[limit(3; repeat(tick))]", path: () };
+//! let modules = loader.load(&arena, program).unwrap();
+//! let filter = Compiler::default()
+//!     .with_funs(funs.chain([tick]))
+//!     .compile(modules)
+//!     .unwrap();
+//!
+//! let data = Data { lut: &filter.lut, ticks: Cell::new(0) };
+//! let ctx = Ctx::<DataKind>::new(&data, Vars::new([]));
+//! let mut out = filter.id.run((ctx, Val::Null)).map(unwrap_valr);
+//!
+//! let expected = [1, 2, 3].map(|n: usize| Val::from(n));
+//! assert_eq!(out.next(), Some(Ok(expected.into_iter().collect())));
+//! assert_eq!(out.next(), None);
+//! assert_eq!(data.ticks.get(), 3);
+//! ~~~
 
 use crate::box_iter::box_once;
 use crate::{Bind, Cv, DataT, Exn, Native, PathsPtr, RunPtr, ValR, ValXs};
@@ -140,4 +140,14 @@ pub trait ValT:
     ///
     /// This is used by `"\(v)"`.
     fn into_string(self) -> Self;
+
+    /// Compare two values with the given operator.
+    ///
+    /// This is used by `l < r`, `l == r`, and so on.
+    /// By default, it uses [`PartialOrd`] and [`PartialEq`];
+    /// override it if the comparison operators should differ from
+    /// the ordering used for sorting, e.g. for NaN.
+    fn cmp_op(&self, op: crate::ops::Cmp, other: &Self) -> bool {
+        op.run(self, other)
+    }
 }
@@ -1,18 +1,38 @@
 pub use jaq_json::{Error, Val, ValR};
 use serde_json::{from_value, Value};
 
-fn yields(x: Val, code: &str, ys: impl Iterator<Item = ValR>) {
+type Filter = jaq_core::Filter<jaq_core::data::JustLut<Val>>;
+
+fn compile(code: &str, spans: bool) -> Filter {
     use jaq_core::load::{Arena, File, Loader};
     eprintln!("{}", code.replace('\n', " "));
 
     let arena = Arena::default();
     let loader = Loader::new(jaq_core::defs());
     let modules = loader.load(&arena, File { path: (), code }).unwrap();
-    let filter = jaq_core::Compiler::default()
+    jaq_core::Compiler::default()
         .with_funs(jaq_core::funs())
+        .with_spans(spans)
         .compile(modules)
-        .unwrap();
-    filter.yields(x, ys)
+        .unwrap()
+}
+
+fn yields(x: Val, code: &str, ys: impl Iterator<Item = ValR>) {
+    compile(code, false).yields(x, ys)
+}
+
+/// Return the span of the first error yielded by the filter `f` on the input `x`.
+///
+/// The filter is compiled with spans if `spans` is true.
+pub fn fail_at(x: Value, f: &str, spans: bool) -> Option<core::ops::Range<usize>> {
+    use jaq_core::{unwrap_valr, Ctx, Vars};
+    let filter = compile(f, spans);
+    let ctx = Ctx::<jaq_core::data::JustLut<Val>>::new(&filter.lut, Vars::new([]));
+    let mut out = filter
+        .id
+        .run((ctx, from_value(x).unwrap()))
+        .map(unwrap_valr);
+    out.find_map(Result::err).unwrap().span().cloned()
 }
 
 pub fn fail(x: Value, f: &str, err: Error) {
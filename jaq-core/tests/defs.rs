@@ -17,8 +17,17 @@ fn all() {
 #[test]
 fn any() {
     give(json!({"a": false, "b": true}), "any", json!(true));
+    give(json!([]), "any", json!(false));
+    give(json!([1, 2]), "any(. > 1)", json!(true));
 }
 
+// `any` and `all` stop at the first truthy and falsy value, respectively,
+// so they terminate even on infinite generators
+yields!(any_infinite, "any(repeat(1); . == 1)", true);
+yields!(all_infinite, "all(repeat(1); . != 1)", false);
+yields!(any_short, "any(true, error; .)", true);
+yields!(all_short, "all(false, error; .)", false);
+
 yields!(join_empty, r#"[] | join(" ")"#, "");
 yields!(
     join_strs,
@@ -26,6 +35,37 @@ yields!(
     "Hello world"
 );
 yields!(join_nums, r#"[2, 3, 4, 5] | join(",")"#, "2,3,4,5");
+#[test]
+fn join_mixed() {
+    let f = r#"join(",")"#;
+    give(json!(["a", 1, null, true]), f, json!("a,1,,true"));
+}
+yields!(del_obj, "{a: 1, b: 2} | del(.a)", json!({"b": 2}));
+yields!(del_arr, "[1, 2, 3] | del(.[0])", [2, 3]);
+yields!(
+    join_arr,
+    r#"["a", [1]] | try join(",") catch ."#,
+    "cannot join [1]"
+);
+
+yields!(
+    combinations,
+    "[[[1, 2], [3, 4]] | combinations]",
+    [[1, 3], [1, 4], [2, 3], [2, 4]]
+);
+yields!(combinations_empty, "[[] | combinations]", json!([[]]));
+yields!(
+    combinations_n,
+    "[[0, 1] | combinations(2)]",
+    [[0, 0], [0, 1], [1, 0], [1, 1]]
+);
+// the product of 12 arrays with 10 elements each has 10^12 elements,
+// so this only terminates quickly if `combinations` is lazy
+yields!(
+    combinations_lazy,
+    "[range(10)] | first(combinations(12))",
+    [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+);
 
 yields!(map, "[1, 2] | map(.+1)", [2, 3]);
 
@@ -60,6 +100,23 @@ fn recurse_obj() {
     gives(json!(2), "recurse(. * .; . < 20)", y);
 }
 
+// descend depth-first, but not into children that fail the condition
+yields!(
+    recurse_cond_order,
+    "[0 | recurse(if . < 3 then .+1, .+2 else empty end; . != 2)]",
+    [0, 1, 3]
+);
+yields!(
+    recurse_cond_path,
+    "[[1, [2]], 3] | [path(recurse(.[]?; . != [2]))]",
+    json!([[], [0], [0, 0], [1]])
+);
+yields!(
+    recurse_cond_update,
+    "[[1, [2]], 3] | recurse(.[]?; . != [2]) |= (.+1)? // .",
+    json!([[2, [2]], 4])
+);
+
 yields!(
     recurse3,
     "[1 | recurse(if . < 3 then .+1 else empty end)]",
@@ -89,6 +146,15 @@ fn repeat() {
     give(json!([0, 1]), "[limit(4; repeat(.[]))]", y);
 }
 
+// these run in constant stack space and memory, because they are tail-recursive
+yields!(repeat_many, "last(limit(100000; repeat(1)))", 1);
+yields!(
+    while_many,
+    "last(limit(100000; 0 | while(true; . + 1)))",
+    99999
+);
+yields!(until_many, "0 | until(. >= 100000; . + 1)", 100000);
+
 yields!(tostring_zero, "1.0 - 1.0 | tostring", "0.0");
 yields!(tostring_str, r#""\n" | tostring"#, "\n");
 yields!(tostring_arr_str, r#"["\n"] | tostring"#, "[\"\\n\"]");
@@ -48,14 +48,34 @@ fn limit() {
     gives(json!(null), "limit(0; 1,2)", []);
     give(json!(null), "[limit(1, 0, 3; 0, 1)]", json!([0, 0, 1]));
 
-    // here, jaq diverges from jq, which returns `[0, 1]`
-    give(json!(null), "[limit(-1; 0, 1)]", json!([]));
+    // negative `$n` yields the last outputs
+    give(json!(null), "[limit(-1; 0, 1)]", json!([1]));
 }
 
+yields!(limit_neg, "[limit(-2; 1, 2, 3)]", [2, 3]);
+yields!(limit_neg_few, "[limit(-3; 1, 2)]", [1, 2]);
+yields!(limit_neg_many, "[limit(-2; range(100000))]", [99998, 99999]);
+yields!(limit_neg_err, "try limit(-2; 1, error(2), 3) catch -.", -2);
+
 yields!(limit_overflow, "[limit(0; def f: f | .; f)]", json!([]));
 
 yields!(limit_path, "[1, 2, 3] | [path(limit(2; .[]))]", [[0], [1]]);
+yields!(
+    limit_neg_path,
+    "[1, 2, 3] | [path(limit(-2; .[]))]",
+    [[1], [2]]
+);
 yields!(skip_path, "[1, 2, 3] | [path(skip(1; .[]))]", [[1], [2]]);
+yields!(nth_path, "[1, 2, 3] | [path(nth(1; .[]))]", [[1]]);
+
+yields!(nth_many, "[nth(0, 2, 3; 1, 2, 3)]", [1, 3]);
+yields!(nth_lazy, "nth(1; 1, 2, error)", 2);
+yields!(nth_inf, "nth(1000; repeat(1))", 1);
+yields!(
+    nth_neg,
+    "try nth(-1; 1, 2) catch .",
+    "Out of bounds negative array index"
+);
 
 yields!(range_pp, "[range(0; 6;  2)]", [0, 2, 4]);
 yields!(range_pn, "[range(0; 6; -2)]", json!([]));
@@ -40,6 +40,59 @@ fn index_access() {
     );
 }
 
+// negative indices count from the end, and
+// out-of-range indices yield `null`, for every way to access an index
+#[test]
+fn index_neg() {
+    let table = [
+        (-1, json!(2)),
+        (-2, json!(1)),
+        (-3, json!(0)),
+        (-4, json!(null)),
+        (-100, json!(null)),
+        (2, json!(2)),
+        (3, json!(null)),
+    ];
+    for (i, y) in table {
+        for f in [".[{i}]", "getpath([{i}])", "nth({i})", "{i} as $i | .[$i]"] {
+            give(
+                json!([0, 1, 2]),
+                &f.replace("{i}", &i.to_string()),
+                y.clone(),
+            );
+        }
+    }
+}
+
+// negative slice bounds count from the end and are clamped to the array
+#[test]
+fn range_neg() {
+    let table = [
+        (Some(-2), None, json!([1, 2]), "bc"),
+        (None, Some(-1), json!([0, 1]), "ab"),
+        (Some(-2), Some(-1), json!([1]), "b"),
+        (Some(-1), Some(-2), json!([]), ""),
+        (Some(-100), None, json!([0, 1, 2]), "abc"),
+        (None, Some(-100), json!([]), ""),
+        (Some(-100), Some(100), json!([0, 1, 2]), "abc"),
+    ];
+    let show = |i: Option<isize>| i.map_or(String::new(), |i| i.to_string());
+    for (from, to, y, ys) in table {
+        let (from, to) = (show(from), show(to));
+        let start = (!from.is_empty()).then(|| format!(r#""start": {from}"#));
+        let end = (!to.is_empty()).then(|| format!(r#""end": {to}"#));
+        let path: Vec<_> = start.into_iter().chain(end).collect();
+
+        give(json!([0, 1, 2]), &format!(".[{from}:{to}]"), y.clone());
+        give(
+            json!([0, 1, 2]),
+            &format!("getpath([{{{}}}])", path.join(", ")),
+            y,
+        );
+        give(json!("abc"), &format!(".[{from}:{to}]"), json!(ys));
+    }
+}
+
 #[test]
 fn iter_access() {
     gives(json!([0, 1, 2]), ".[]", [json!(0), json!(1), json!(2)]);
@@ -50,6 +103,15 @@ fn iter_access() {
     gives(json!("asdf"), ".[]?", []);
 }
 
+// iterating over values that are still referenced elsewhere
+yields!(iter_shared_arr, "[1, 2] as $x | [$x[], $x[]]", [1, 2, 1, 2]);
+yields!(
+    iter_shared_obj,
+    r#"{"a": 1, "b": 2} as $x | [$x[], ($x | to_entries[].key), $x.a]"#,
+    json!([1, 2, "a", "b", 1])
+);
+yields!(iter_shared_first, "[range(10)] as $x | first($x[])", 0);
+
 #[test]
 fn range_access() {
     give(json!("Möwe"), ".[1:-1]", json!("öw"));
@@ -128,6 +190,29 @@ fn iter_update() {
     give(json!([[1]]), ".[][] |= .+1", json!([[2]]));
 }
 
+yields!(
+    iter_update_obj,
+    r#"{"a": 1, "b": 2} | .[] |= .+1"#,
+    json!({"a": 2, "b": 3})
+);
+// updating object values preserves the order of keys
+yields!(
+    iter_update_obj_order,
+    r#"{"c": 1, "a": 2, "b": 3} | .[] |= .*10 | [keys_unsorted, [.[]]]"#,
+    json!([["c", "a", "b"], [10, 20, 30]])
+);
+// values for which the update yields no output are removed
+yields!(
+    iter_update_obj_empty,
+    r#"{"c": 1, "a": 2, "b": 3} | .[] |= if . == 2 then empty end | keys_unsorted"#,
+    ["c", "b"]
+);
+yields!(
+    iter_update_obj_nested,
+    r#"{"a": {"x": 1}, "b": {"y": 2}} | .[][] += 1"#,
+    json!({"a": {"x": 2}, "b": {"y": 3}})
+);
+
 yields!(
     obj_iter_update,
     r#"{"a": 1, "b": 2} | .[] |= ((if .>1 then . else {}[] end) | .+1)"#,
@@ -156,6 +241,28 @@ fn range_update() {
     );
 }
 
+// assigning to a slice splices the new value into the array
+#[test]
+fn range_assign() {
+    let x = || json!([0, 1, 2, 3]);
+    give(x(), r#".[1:3] = ["x"]"#, json!([0, "x", 3]));
+    give(x(), r#".[1:2] = ["x", "y"]"#, json!([0, "x", "y", 2, 3]));
+    give(x(), r#".[1:3] = []"#, json!([0, 3]));
+    give(x(), ".[0:] = []", json!([]));
+
+    // empty slices insert the new value
+    give(x(), r#".[1:1] = ["x"]"#, json!([0, "x", 1, 2, 3]));
+    give(x(), r#".[2:1] = ["x"]"#, json!([0, 1, "x", 2, 3]));
+
+    // out-of-range bounds are clamped
+    give(x(), r#".[8:9] = ["x"]"#, json!([0, 1, 2, 3, "x"]));
+    give(x(), r#".[-8:1] = ["x"]"#, json!(["x", 1, 2, 3]));
+    give(x(), r#".[-2:9] = ["x"]"#, json!([0, 1, "x"]));
+
+    give(json!("abcd"), r#".[1:3] = "xyz""#, json!("axyzd"));
+    give(json!([]), r#"try (.[0:1] = 1) catch "fail""#, json!("fail"));
+}
+
 // Test what happens when update filter returns multiple values.
 // Watch out: here, jaq diverges frequently from jq;
 // jq considers only the first value of the filter regardless of the updated value,
@@ -190,3 +297,27 @@ fn update_complex() {
 
 yields!(alt_update_l, "[1, 2] | .[] // . |= 3", [3, 3]);
 yields!(alt_update_r, "[] | .[] // . |= 3", 3);
+yields!(
+    alt_update_obj,
+    "[{a: false, b: 1}, {a: 2, b: 1} | (.a // .b) |= . + 1]",
+    json!([{"a": false, "b": 2}, {"a": 3, "b": 1}])
+);
+// an error in the update does not fall back to the right alternative
+yields!(
+    alt_update_err,
+    "{a: 1} | try ((.a // .b) |= error) catch .",
+    1
+);
+
+// the condition is run once on the original input, and
+// every output of the condition updates through the corresponding branch
+yields!(
+    ite_update_once,
+    "[0] | (if (true, true, true) then .[0] else . end) |= . + 1",
+    [3]
+);
+yields!(
+    ite_update_orig,
+    "{a: 1, b: 1} | (if .a == 1, .a == 1 then .a else .b end) |= . + 1",
+    json!({"a": 3, "b": 1})
+);
@@ -2,7 +2,7 @@
 
 pub mod common;
 
-use common::{give, gives};
+use common::{fail, fail_at, give, gives, Error};
 use serde_json::json;
 
 #[test]
@@ -77,6 +77,20 @@ yields!(div_str_empty_sep, r#""aöß" / """#, ["a", "ö", "ß"]);
 // negating it and subtracting 1 from it keeps it a machine-sized integer.
 yields!(rem_isize_min, "(-9223372036854775807 - 1) % -1", 0);
 
+// integers beyond 2^53 are not converted to floats,
+// and integers beyond `isize` transparently become big integers
+yields!(add_int_exact, "9007199254740993 + 1 - 9007199254740992", 2);
+yields!(
+    mul_int_exact,
+    "3 * 3074457345618258603 - 9223372036854775808",
+    1
+);
+yields!(
+    sub_int_overflow,
+    "-9223372036854775807 - 3 + 9223372036854775810",
+    0
+);
+
 #[test]
 fn logic() {
     let tf = json!([true, false]);
@@ -98,6 +112,11 @@ fn try_() {
     give(json!(0), ".?", json!(0));
     give(json!(0), r#"(-"a")?, 1"#, json!(1));
     give(json!(0), r#"[(1, -"a", 2)?]"#, json!([1]));
+
+    // `?` on paths keeps its meaning and still yields paths
+    give(json!({"a": 1}), "[.a.b?]", json!([]));
+    give(json!([1, {"b": 2}]), "[.[]?.b?]", json!([2]));
+    give(json!({"a": 1}), ".c? |= 3", json!({"a": 1, "c": 3}));
 }
 
 #[test]
@@ -252,6 +271,22 @@ yields!(
     json!([])
 );
 
+yields!(
+    label_break_foreach,
+    "[label $out | foreach (1, 2, 3, 4) as $x (0; . + $x; if . > 3 then ., break $out end)]",
+    [1, 3, 6]
+);
+yields!(
+    label_break_reduce,
+    "[label $out | reduce (1, 2, 3) as $x (0; if $x > 1 then break $out end | . + $x)]",
+    json!([])
+);
+yields!(
+    label_break_nested,
+    "[label $a | range(3) as $x | range(3) as $y | if $x + $y > 2 then break $a end | [$x, $y]]",
+    [[0, 0], [0, 1], [0, 2], [1, 0], [1, 1]]
+);
+
 yields!(
     try_catch_short_circuit,
     r#"[try ("1", "2", []["3"], "4") catch .]"#,
@@ -268,6 +303,86 @@ yields!(
     [0, 1, 2, 3]
 );
 yields!(try_without_catch, "[try (1,2,3[0],4)]", [1, 2]);
+// the handler receives the error value and can re-raise selected errors
+yields!(
+    try_catch_reraise,
+    r#"try [("b", {c: 1}, "a", "d") | try error catch if . == "a" then error end] catch "re: \(.)""#,
+    "re: a"
+);
+yields!(
+    try_catch_value,
+    r#"[("b", {c: 1}) | try error catch if . == "a" then error end]"#,
+    json!(["b", {"c": 1}])
+);
+// error payloads of any type are caught with their structure intact
+#[test]
+fn try_catch_structured() {
+    let x = json!([{"a": [1, {"b": null}]}, [1, "x"], 1.5, true, null, "s"]);
+    give(x.clone(), "map(try error catch .)", x);
+}
+yields!(
+    try_catch_structured_field,
+    r#"try error({code: 404, tags: ["x"]}) catch [.code + 1, .tags[0]]"#,
+    json!([405, "x"])
+);
+// re-raising an error preserves its payload
+yields!(
+    try_catch_structured_reraise,
+    r#"try (try error({a: 1}) catch error) catch .a"#,
+    1
+);
+
+// values in error messages are cut off after 30 characters
+yields!(
+    error_preview_long,
+    r#"[range(100)] | try (. + "a") catch ."#,
+    r#"cannot calculate [0,1,2,3,4,5,6,7,8,9,10,11,12,... + "a""#
+);
+yields!(
+    error_preview_short,
+    r#"[range(3)] | try (. + "a") catch ."#,
+    r#"cannot calculate [0,1,2] + "a""#
+);
+// payloads given to `error` are never cut off
+yields!(
+    error_preview_payload,
+    "[range(100)] | try error catch .[99]",
+    99
+);
+
+// errors point to the innermost call in the main program
+// to a filter defined outside of it
+#[test]
+fn error_span() {
+    assert_eq!(fail_at(json!(1), "1 | error", true), Some(4..9));
+    assert_eq!(fail_at(json!(1), "def f: error; f", true), Some(7..12));
+    assert_eq!(fail_at(json!(1), "limit(1; error)", true), Some(9..14));
+    assert_eq!(
+        fail_at(json!(1), "try error catch error", true),
+        Some(16..21)
+    );
+}
+
+#[test]
+fn error_span_none() {
+    assert_eq!(fail_at(json!(1), r#"1 + "a""#, true), None);
+    assert_eq!(fail_at(json!(1), "def f: .[0]; f", true), None);
+}
+
+#[test]
+fn error_span_disabled() {
+    assert_eq!(fail_at(json!(1), "1 | error", false), None);
+}
+
+// uncaught errors also carry the original value
+#[test]
+fn error_structured_uncaught() {
+    let x = || json!({"a": [1, null]});
+    let v = || serde_json::from_value(x()).unwrap();
+    fail(x(), "error", Error::new(v()));
+    assert_eq!(Error::new(v()).into_val(), v());
+}
+
 yields!(
     try_catch_prefix_operation,
     r#"(try -[] catch .) | . > "" and . < []"#,
@@ -400,6 +515,12 @@ yields!(
     [[1, 1], [2, 3], [3, 6]]
 );
 
+yields!(
+    foreach_infinite_limit,
+    "[limit(3; foreach repeat(1) as $x (0; .+$x; [$x, .]))]",
+    [[1, 1], [1, 2], [1, 3]]
+);
+
 // jq will give only [4, 3, 7, 12] here because
 // it keeps only the *last* output value as input value for the next iteration, whereas
 // jaq keeps all output values as input values
@@ -414,6 +535,18 @@ yields!(update_alt, "[[0!=0, 3] | .[] //= (1, 2)]", [[1, 3], [2, 3]]);
 const FIRST: &str = "def first(f): label $x | f | ., break $x;";
 
 yields!(first, &(FIRST.to_owned() + "first(1, 2, 3)"), 1);
+// a break to an outer label passes through `first`
+yields!(
+    first_break_outer,
+    &(FIRST.to_owned() + "[(label $a | first(break $a, 1)), 2]"),
+    [2]
+);
+// the break of `first` does not leak past its label
+yields!(
+    first_break_inner,
+    &(FIRST.to_owned() + "[label $a | first(1, break $a), 2]"),
+    [1, 2]
+);
 
 const LIMIT: &str = "def limit($n; f):
   foreach f as $x ({$n}; {$x, n: .n - 1} | if .n < 0 then {}[] end) | .x;";
@@ -527,3 +660,73 @@ yields!(
     "[[1, [[2]]]] | foreach (0, 1, 0) as $p (.; .[$p]; if $p == 0 then .[0] else {}[] as $x | . end) += 1",
     json!([[2, [[3]]]])
 );
+
+yields!(loc, "$__loc__", json!({"file": "<top-level>", "line": 1}));
+yields!(loc_line, "[1,\n2,\n$__loc__.line]", [1, 2, 3]);
+yields!(loc_shadow, "0 as $__loc__ | $__loc__", 0);
+
+yields!(builtins_def, r#"builtins | any(.[]; . == "map/1")"#, true);
+yields!(
+    builtins_native,
+    r#"builtins | any(.[]; . == "path/1")"#,
+    true
+);
+yields!(
+    builtins_user,
+    r#"def f: 1; builtins | any(.[]; . == "f/0" or . == "!empty/0")"#,
+    false
+);
+yields!(
+    builtins_self,
+    r#"builtins | any(.[]; . == "builtins/0")"#,
+    true
+);
+yields!(
+    builtins_internal,
+    r#"builtins | any(.[]; . == "error_empty/0" or . == "min_or_empty/0")"#,
+    false
+);
+
+#[test]
+fn depth_limit() {
+    use jaq_core::load::{Arena, File, Loader};
+    use jaq_core::{data, unwrap_valr, Ctx, Vars};
+    use jaq_json::Val;
+
+    let run = |code, depth| {
+        let arena = Arena::default();
+        let loader = Loader::new(jaq_core::defs());
+        let modules = loader.load(&arena, File { path: (), code }).unwrap();
+        let filter = jaq_core::Compiler::default()
+            .with_funs(jaq_core::funs())
+            .compile(modules)
+            .unwrap();
+        let ctx = Ctx::<data::JustLut<Val>>::new(&filter.lut, Vars::new([]));
+        let ctx = ctx.with_depth_limit(depth);
+        let out = filter.id.run((ctx, Val::from(0isize))).map(unwrap_valr);
+        out.collect::<Vec<_>>()
+    };
+    let msg = "recursion limit exceeded";
+    let exceeded = || vec![Err(common::Error::str(msg))];
+
+    let f = "def f: if . < 20 then .+1 | f | .+1 else . end; f";
+    assert_eq!(run(f, 100), [Ok(Val::from(40isize))]);
+    assert_eq!(run(f, 10), exceeded());
+    let f = "def f: if . < 20 then .+1 | f | .+1 else . end; try f catch .";
+    assert_eq!(run(f, 10), [Ok(Val::from(msg.to_string()))]);
+
+    // the same holds for paths and updates
+    let f = "def f($n): if $n > 0 then .[0] | f($n - 1) | .[0] end;";
+    let path = f.to_owned() + "[] | [path(f(20))] | .[0][39]";
+    assert_eq!(run(&path, 100), [Ok(Val::from(0isize))]);
+    assert_eq!(run(&path, 10), exceeded());
+    let update = f.to_owned() + "[[[[[[0]]]]]] | f(3) |= 1 | .[0][0][0][0][0][0]";
+    assert_eq!(run(&update, 100), [Ok(Val::from(1isize))]);
+    assert_eq!(run(&update, 2), exceeded());
+
+    // tail-recursive calls do not count towards the limit
+    assert_eq!(
+        run("last(limit(1000; repeat(1)))", 10),
+        [Ok(Val::from(1isize))]
+    );
+}
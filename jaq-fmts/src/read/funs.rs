@@ -1,10 +1,10 @@
-use super::{cbor, tabular, toml, xml, yaml};
+use super::{cbor, msgpack, tabular, toml, xml, yaml};
 use alloc::boxed::Box;
 use bytes::Bytes;
 use core::fmt;
 use jaq_core::box_iter::{then, BoxIter};
 use jaq_core::native::{bome, v, Filter};
-use jaq_core::{DataT, Exn, RunPtr};
+use jaq_core::{DataT, Exn, RunPtr, ValT as _};
 use jaq_json::{bytes_valrs, Error, Val, ValR, ValX};
 use jaq_std::ValT as _;
 
@@ -17,6 +17,13 @@ pub fn funs<D: for<'a> DataT<V<'a> = Val>>() -> Box<[Filter<RunPtr<D>>]> {
                 bytes_valrs(s, |s| Box::new(cbor::parse_many(s).map(fail)))
             }))
         }),
+        ("frommsgpack", v(0), |cv| {
+            bmme(then(cv.1.try_as_bytes_owned(), |s| {
+                let fail =
+                    move |r: Result<_, _>| r.map_err(|e| parse_fail(&cv.1, "MessagePack", e));
+                bytes_valrs(s, |s| Box::new(msgpack::parse_many(s).map(fail)))
+            }))
+        }),
         ("fromyaml", v(0), |cv| {
             bmme(then(cv.1.try_as_utf8_bytes_owned(), |s| {
                 let fail = move |r: Result<_, _>| r.map_err(|e| parse_fail(&cv.1, "YAML", e));
@@ -35,11 +42,18 @@ pub fn funs<D: for<'a> DataT<V<'a> = Val>>() -> Box<[Filter<RunPtr<D>>]> {
             bome(cv.1.try_as_utf8_bytes().and_then(from_utf8).and_then(parse))
         }),
         ("fromcsv", v(0), |cv| {
-            bmme(then(cv.1.try_as_bytes_owned(), |s| {
-                bytes_valrs(s, |s| {
-                    Box::new(tabular::read_csv(s.iter().copied().map(Ok)))
-                })
-            }))
+            fromcsv(cv.1.try_as_bytes_owned().map(|s| (b',', s)), false)
+        }),
+        ("fromcsv", v(1), |mut cv| {
+            let sep = csv_sep(cv.0.pop_var());
+            let s = sep.and_then(|sep| Ok((sep, cv.1.try_as_bytes_owned()?)));
+            fromcsv(s, false)
+        }),
+        ("fromcsv", v(2), |mut cv| {
+            let header = cv.0.pop_var().as_bool();
+            let sep = csv_sep(cv.0.pop_var());
+            let s = sep.and_then(|sep| Ok((sep, cv.1.try_as_bytes_owned()?)));
+            fromcsv(s, header)
         }),
         ("fromtsv", v(0), |cv| {
             bmme(then(cv.1.try_as_bytes_owned(), |s| {
@@ -56,8 +70,29 @@ fn bmme<'a>(iter: BoxIter<'a, ValR>) -> BoxIter<'a, ValX<'a>> {
     Box::new(iter.map(|r| r.map_err(Exn::from)))
 }
 
-fn parse_fail(i: &impl fmt::Display, fmt: &str, e: impl fmt::Display) -> Error {
-    Error::str(format_args!("cannot parse {i} as {fmt}: {e}"))
+fn csv_sep(sep: Val) -> Result<u8, Error> {
+    match sep.try_as_utf8_bytes() {
+        Ok([sep]) if !matches!(sep, b'"' | b'\r' | b'\n') => Ok(*sep),
+        _ => Err(Error::with_val("invalid CSV separator: ", sep, "")),
+    }
+}
+
+/// Read CSV rows with the given separator, as objects if `header` is true.
+fn fromcsv<'a>(s: Result<(u8, Bytes), Error>, header: bool) -> BoxIter<'a, ValX<'a>> {
+    bmme(then(s, |(sep, s)| {
+        bytes_valrs(s, |s| {
+            let rows = tabular::read_csv_sep(s.iter().copied().map(Ok), sep);
+            if header {
+                Box::new(tabular::with_header(rows))
+            } else {
+                Box::new(rows)
+            }
+        })
+    }))
+}
+
+fn parse_fail(i: &Val, fmt: &str, e: impl fmt::Display) -> Error {
+    Error::with_val("cannot parse ", i.clone(), alloc::format!(" as {fmt}: {e}"))
 }
 
 type ValRs<'a> = BoxIter<'a, ValR>;
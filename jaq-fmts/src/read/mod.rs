@@ -1,6 +1,8 @@
 //! Read values in different formats.
 #[cfg(feature = "cbor")]
 pub mod cbor;
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
 pub mod tabular;
 #[cfg(feature = "toml")]
 pub mod toml;
@@ -0,0 +1,166 @@
+//! MessagePack support.
+//!
+//! This implements the
+//! [MessagePack specification](https://github.com/msgpack/msgpack/blob/master/spec.md),
+//! except for extension types, which are rejected.
+use alloc::vec::Vec;
+use core::fmt::{self, Formatter};
+use jaq_json::{Num, Val};
+
+/// Limit pre-allocated container capacity to this number of elements.
+///
+/// This serves the same purpose as in the CBOR parser, namely to
+/// prevent running out of memory on user-given container sizes.
+const MAX_CAP: usize = 1024;
+
+/// Maximal nesting depth of arrays and maps.
+///
+/// This prevents overflowing the stack on deeply nested input.
+const DEPTH: usize = 1024;
+
+/// Parse error.
+#[derive(Debug)]
+pub enum Error {
+    /// unexpected end of input
+    Eof,
+    /// marker byte that is never used
+    Marker(u8),
+    /// unsupported extension type
+    Ext(i8),
+    /// arrays or maps nested too deeply
+    Depth,
+}
+
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Eof => write!(f, "unexpected end of input"),
+            Self::Marker(m) => write!(f, "invalid marker byte: {m:#04x}"),
+            Self::Ext(t) => write!(f, "unsupported extension type: {t}"),
+            Self::Depth => write!(f, "maximal nesting depth exceeded"),
+        }
+    }
+}
+
+/// Parse a sequence of MessagePack values.
+pub fn parse_many(b: &[u8]) -> impl Iterator<Item = Result<Val, Error>> + '_ {
+    let mut p = Parser(b);
+    core::iter::from_fn(move || (!p.0.is_empty()).then(|| p.parse(DEPTH)))
+}
+
+struct Parser<'a>(&'a [u8]);
+
+impl<'a> Parser<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], Error> {
+        if n > self.0.len() {
+            return Err(Error::Eof);
+        }
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn take_arr<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    /// Parse an unsigned big-endian integer of `n` bytes.
+    fn uint(&mut self, n: usize) -> Result<u64, Error> {
+        let b = self.take(n)?;
+        Ok(b.iter().fold(0, |acc, b| acc << 8 | u64::from(*b)))
+    }
+
+    /// Parse a length of `n` bytes.
+    fn len(&mut self, n: usize) -> Result<usize, Error> {
+        // on 32-bit systems, lengths that do not fit into `usize`
+        // cannot be satisfied by the input anyway
+        self.uint(n)
+            .map(|l| usize::try_from(l).unwrap_or(usize::MAX))
+    }
+
+    fn many<T>(
+        &mut self,
+        len: usize,
+        f: impl Fn(&mut Self) -> Result<T, Error>,
+    ) -> Result<Vec<T>, Error> {
+        let mut a = Vec::with_capacity(len.min(MAX_CAP));
+        for _ in 0..len {
+            a.push(f(self)?);
+        }
+        Ok(a)
+    }
+
+    fn arr(&mut self, len: usize, depth: usize) -> Result<Val, Error> {
+        let depth = depth.checked_sub(1).ok_or(Error::Depth)?;
+        Ok(Val::Arr(self.many(len, |p| p.parse(depth))?.into()))
+    }
+
+    fn obj(&mut self, len: usize, depth: usize) -> Result<Val, Error> {
+        let depth = depth.checked_sub(1).ok_or(Error::Depth)?;
+        let o = self.many(len, |p| Ok((p.parse(depth)?, p.parse(depth)?)))?;
+        Ok(Val::obj(o.into_iter().collect()))
+    }
+
+    fn str(&mut self, len: usize) -> Result<Val, Error> {
+        Ok(Val::utf8_str(self.take(len)?.to_vec()))
+    }
+
+    fn bin(&mut self, len: usize) -> Result<Val, Error> {
+        Ok(Val::byte_str(self.take(len)?.to_vec()))
+    }
+
+    fn ext(&mut self, len: usize) -> Result<Val, Error> {
+        let [typ] = self.take_arr()?;
+        self.take(len)?;
+        Err(Error::Ext(typ as i8))
+    }
+
+    /// Parse a value, failing if it contains arrays or maps nested deeper than `depth`.
+    fn parse(&mut self, depth: usize) -> Result<Val, Error> {
+        let [marker] = self.take_arr()?;
+        let int = |i| Ok(Val::Num(Num::from_integral(i)));
+        match marker {
+            0x00..=0x7f => int(i64::from(marker)),
+            0x80..=0x8f => self.obj(usize::from(marker & 0x0f), depth),
+            0x90..=0x9f => self.arr(usize::from(marker & 0x0f), depth),
+            0xa0..=0xbf => self.str(usize::from(marker & 0x1f)),
+            0xc0 => Ok(Val::Null),
+            0xc2 => Ok(Val::Bool(false)),
+            0xc3 => Ok(Val::Bool(true)),
+            0xc4 => self.len(1).and_then(|l| self.bin(l)),
+            0xc5 => self.len(2).and_then(|l| self.bin(l)),
+            0xc6 => self.len(4).and_then(|l| self.bin(l)),
+            0xc7 => self.len(1).and_then(|l| self.ext(l)),
+            0xc8 => self.len(2).and_then(|l| self.ext(l)),
+            0xc9 => self.len(4).and_then(|l| self.ext(l)),
+            0xca => Ok(Val::from(f64::from(f32::from_be_bytes(self.take_arr()?)))),
+            0xcb => Ok(Val::from(f64::from_be_bytes(self.take_arr()?))),
+            0xcc => int(i64::from(u8::from_be_bytes(self.take_arr()?))),
+            0xcd => int(i64::from(u16::from_be_bytes(self.take_arr()?))),
+            0xce => int(i64::from(u32::from_be_bytes(self.take_arr()?))),
+            0xcf => Ok(Val::Num(Num::from_integral(u64::from_be_bytes(
+                self.take_arr()?,
+            )))),
+            0xd0 => int(i64::from(i8::from_be_bytes(self.take_arr()?))),
+            0xd1 => int(i64::from(i16::from_be_bytes(self.take_arr()?))),
+            0xd2 => int(i64::from(i32::from_be_bytes(self.take_arr()?))),
+            0xd3 => int(i64::from_be_bytes(self.take_arr()?)),
+            0xd4 => self.ext(1),
+            0xd5 => self.ext(2),
+            0xd6 => self.ext(4),
+            0xd7 => self.ext(8),
+            0xd8 => self.ext(16),
+            0xd9 => self.len(1).and_then(|l| self.str(l)),
+            0xda => self.len(2).and_then(|l| self.str(l)),
+            0xdb => self.len(4).and_then(|l| self.str(l)),
+            0xdc => self.len(2).and_then(|l| self.arr(l, depth)),
+            0xdd => self.len(4).and_then(|l| self.arr(l, depth)),
+            0xde => self.len(2).and_then(|l| self.obj(l, depth)),
+            0xdf => self.len(4).and_then(|l| self.obj(l, depth)),
+            0xe0..=0xff => int(i64::from(marker as i8)),
+            0xc1 => Err(Error::Marker(marker)),
+        }
+    }
+}
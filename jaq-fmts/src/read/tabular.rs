@@ -63,8 +63,8 @@ where
     Ok(field)
 }
 
-fn csv_field<E>(iter: &mut impl Iterator<Item = Result<u8, E>>) -> Result<Field, E> {
-    field(iter, b',', b'"', |iter, field| loop {
+fn csv_field<E>(iter: &mut impl Iterator<Item = Result<u8, E>>, sep: u8) -> Result<Field, E> {
+    field(iter, sep, b'"', |iter, field| loop {
         match iter.next().transpose()? {
             Some(b'"') => match iter.next().transpose()? {
                 Some(b'"') => field.bytes.push(b'"'),
@@ -101,7 +101,7 @@ fn tsv_field<E>(iter: &mut impl Iterator<Item = Result<u8, E>>) -> Result<Field,
 /// Read single table row.
 fn row<E, I: Iterator<Item = Result<u8, E>>>(
     iter: &mut I,
-    field: fn(&mut I) -> Result<Field, E>,
+    field: impl Fn(&mut I) -> Result<Field, E>,
 ) -> Option<Result<Val, E>> {
     let mut fields = Vec::new();
     loop {
@@ -122,9 +122,40 @@ fn row<E, I: Iterator<Item = Result<u8, E>>>(
 
 /// Read rows of a CSV file.
 pub fn read_csv<E>(
+    iter: impl Iterator<Item = Result<u8, E>>,
+) -> impl Iterator<Item = Result<Val, E>> {
+    read_csv_sep(iter, b',')
+}
+
+/// Read rows of a CSV file whose fields are separated by `sep` instead of `,`.
+pub fn read_csv_sep<E>(
     mut iter: impl Iterator<Item = Result<u8, E>>,
+    sep: u8,
+) -> impl Iterator<Item = Result<Val, E>> {
+    core::iter::from_fn(move || row(&mut iter, |iter| csv_field(iter, sep)))
+}
+
+/// Convert rows to objects whose keys are the fields of the first row.
+///
+/// Missing fields are `null` and fields without a key are dropped.
+pub fn with_header<E>(
+    mut rows: impl Iterator<Item = Result<Val, E>>,
 ) -> impl Iterator<Item = Result<Val, E>> {
-    core::iter::from_fn(move || row(&mut iter, csv_field))
+    let mut keys = None;
+    core::iter::from_fn(move || loop {
+        let row = match rows.next()? {
+            Ok(Val::Arr(row)) => row,
+            r => return Some(r),
+        };
+        match &keys {
+            None => keys = Some(row),
+            Some(keys) => {
+                let vals = row.iter().cloned().chain(core::iter::repeat(Val::Null));
+                let obj = keys.iter().cloned().zip(vals).collect();
+                return Some(Ok(Val::obj(obj)));
+            }
+        }
+    })
 }
 
 /// Read rows of a TSV file.
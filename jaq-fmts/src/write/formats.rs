@@ -30,8 +30,14 @@ pub fn write(w: &mut dyn Write, writer: &Writer, val: &Val) -> Result {
         (_, Format::Yaml) => yaml::write(w, pp, 0, val)?,
         (_, Format::Toml) => write!(w, "{}", map_err_to_string(toml::Root::try_from(val))?)?,
         (_, Format::Xml) => map_err_to_string(xml::Xml::try_from(val))?.write(w)?,
-        (_, Format::Csv) => map_err_to_string(tabular::Row::try_from(val))?.write_csv(w)?,
-        (_, Format::Tsv) => map_err_to_string(tabular::Row::try_from(val))?.write_tsv(w)?,
+        (_, Format::Csv | Format::Tsv) => {
+            let row = map_err_to_string(tabular::Row::try_from(val))?;
+            let row = row.with_float_prec(pp.float_prec);
+            match format {
+                Format::Csv => row.write_csv(w)?,
+                _ => row.write_tsv(w)?,
+            }
+        }
     };
 
     w.write_all(match format {
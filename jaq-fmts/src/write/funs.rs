@@ -1,4 +1,4 @@
-use super::{cbor, tabular, toml, xml, yaml};
+use super::{cbor, msgpack, tabular, toml, xml, yaml};
 use alloc::{boxed::Box, string::ToString, vec::Vec};
 use core::fmt;
 use jaq_core::box_iter::box_once;
@@ -26,6 +26,12 @@ pub fn funs<D: for<'a> DataT<V<'a> = Val>>() -> Box<[Filter<RunPtr<D>>]> {
             cbor::write(&mut buf, &cv.1).unwrap();
             bome(Ok(Val::byte_str(buf)))
         }),
+        ("tomsgpack", v(0), |cv| {
+            let mut buf = Vec::new();
+            let ser = msgpack::encode(&cv.1, &mut buf);
+            let fail = |e| serialise_fail(&cv.1, "MessagePack", e);
+            bome(ser.map(|()| Val::byte_str(buf)).map_err(fail))
+        }),
         ("toyaml", v(0), |cv| {
             let mut buf = Vec::new();
             let pp = Pp {
@@ -47,6 +53,10 @@ pub fn funs<D: for<'a> DataT<V<'a> = Val>>() -> Box<[Filter<RunPtr<D>>]> {
     ])
 }
 
-fn serialise_fail(i: &impl fmt::Display, fmt: &str, e: impl fmt::Display) -> Error {
-    Error::str(format_args!("cannot serialise {i} as {fmt}: {e}"))
+fn serialise_fail(i: &Val, fmt: &str, e: impl fmt::Display) -> Error {
+    Error::with_val(
+        "cannot serialise ",
+        i.clone(),
+        alloc::format!(" as {fmt}: {e}"),
+    )
 }
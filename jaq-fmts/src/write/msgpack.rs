@@ -0,0 +1,129 @@
+//! MessagePack support.
+use alloc::vec::Vec;
+use core::fmt::{self, Formatter};
+use jaq_json::{Num, Val};
+
+/// Serialisation error.
+#[derive(Debug)]
+pub enum Error {
+    /// integer that does not fit into 64 bits
+    Int(Val),
+    /// string, array, or object with more than `u32::MAX` elements
+    Len(usize),
+}
+
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Int(v) => write!(f, "integer does not fit into 64 bits: {v}"),
+            Self::Len(l) => write!(f, "length exceeds 32 bits: {l}"),
+        }
+    }
+}
+
+/// Encode a value as MessagePack.
+pub fn encode(v: &Val, buf: &mut Vec<u8>) -> Result<(), Error> {
+    match v {
+        Val::Null => buf.push(0xc0),
+        Val::Bool(b) => buf.push(if *b { 0xc3 } else { 0xc2 }),
+        Val::Num(Num::Int(i)) => int(*i as i64, buf),
+        Val::Num(Num::BigInt(i)) => {
+            if let Ok(i) = i64::try_from(&**i) {
+                int(i, buf)
+            } else if let Ok(u) = u64::try_from(&**i) {
+                buf.push(0xcf);
+                buf.extend(u.to_be_bytes())
+            } else {
+                return Err(Error::Int(v.clone()));
+            }
+        }
+        Val::Num(Num::Float(f)) => {
+            buf.push(0xcb);
+            buf.extend(f.to_be_bytes())
+        }
+        Val::Num(Num::Dec(d)) => encode(&Val::Num(Num::from_dec_str(d)), buf)?,
+        Val::TStr(s) => {
+            len(s.len(), (0xa0, 32), Some(0xd9), [0xda, 0xdb], buf)?;
+            buf.extend(&**s)
+        }
+        Val::BStr(b) => {
+            len(b.len(), (0, 0), Some(0xc4), [0xc5, 0xc6], buf)?;
+            buf.extend(&**b)
+        }
+        Val::Arr(a) => {
+            len(a.len(), (0x90, 16), None, [0xdc, 0xdd], buf)?;
+            a.iter().try_for_each(|x| encode(x, buf))?
+        }
+        Val::Obj(o) => {
+            len(o.len(), (0x80, 16), None, [0xde, 0xdf], buf)?;
+            o.iter().try_for_each(|(k, v)| {
+                encode(k, buf)?;
+                encode(v, buf)
+            })?
+        }
+    }
+    Ok(())
+}
+
+/// Encode an integer in the smallest possible representation.
+fn int(i: i64, buf: &mut Vec<u8>) {
+    if let Ok(u) = u64::try_from(i) {
+        if u < 0x80 {
+            buf.push(u as u8)
+        } else if let Ok(u) = u8::try_from(u) {
+            buf.extend([0xcc, u])
+        } else if let Ok(u) = u16::try_from(u) {
+            buf.push(0xcd);
+            buf.extend(u.to_be_bytes())
+        } else if let Ok(u) = u32::try_from(u) {
+            buf.push(0xce);
+            buf.extend(u.to_be_bytes())
+        } else {
+            buf.push(0xcf);
+            buf.extend(u.to_be_bytes())
+        }
+    } else if i >= -32 {
+        buf.push(i as u8)
+    } else if let Ok(i) = i8::try_from(i) {
+        buf.extend([0xd0, i as u8])
+    } else if let Ok(i) = i16::try_from(i) {
+        buf.push(0xd1);
+        buf.extend(i.to_be_bytes())
+    } else if let Ok(i) = i32::try_from(i) {
+        buf.push(0xd2);
+        buf.extend(i.to_be_bytes())
+    } else {
+        buf.push(0xd3);
+        buf.extend(i.to_be_bytes())
+    }
+}
+
+/// Encode the length of a string or container.
+///
+/// For lengths smaller than `fix`, this uses the marker `fixed` combined with the length.
+/// Otherwise, this uses the marker for 8-bit lengths (if there is one),
+/// 16-bit lengths, or 32-bit lengths.
+fn len(
+    l: usize,
+    (fixed, fix): (u8, usize),
+    m8: Option<u8>,
+    [m16, m32]: [u8; 2],
+    buf: &mut Vec<u8>,
+) -> Result<(), Error> {
+    if l < fix {
+        buf.push(fixed | l as u8)
+    } else if let (Some(m8), Ok(l)) = (m8, u8::try_from(l)) {
+        buf.extend([m8, l])
+    } else if let Ok(l) = u16::try_from(l) {
+        buf.push(m16);
+        buf.extend(l.to_be_bytes())
+    } else if let Ok(l) = u32::try_from(l) {
+        buf.push(m32);
+        buf.extend(l.to_be_bytes())
+    } else {
+        return Err(Error::Len(l));
+    }
+    Ok(())
+}
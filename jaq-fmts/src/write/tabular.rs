@@ -1,10 +1,13 @@
 //! CSV and TSV support.
 use core::fmt::{self, Display, Formatter};
-use jaq_json::Val;
+use jaq_json::{Num, Val};
 use std::io;
 
 /// CSV/TSV row, i.e. a list of fields.
-pub struct Row(Vec<Val>);
+pub struct Row {
+    fields: Vec<Val>,
+    float_prec: Option<usize>,
+}
 
 /// Serialisation error.
 pub enum Error {
@@ -32,18 +35,74 @@ impl TryFrom<&Val> for Row {
                 Val::Null | Val::Bool(_) | Val::Num(_) | Val::TStr(_) => Ok(v.clone()),
                 _ => Err(Error::Field(v.clone())),
             });
-            iter.collect::<Result<_, _>>().map(Self)
+            let fields = iter.collect::<Result<_, _>>()?;
+            Ok(Self {
+                fields,
+                float_prec: None,
+            })
         } else {
             Err(Error::Row(v.clone()))
         }
     }
 }
 
+/// Display a number as table field.
+///
+/// Spreadsheets may not recognise numbers in exponent notation, so
+/// unless a precision is given, we write non-integer numbers
+/// whose magnitude is in `[1e-6, 1e21)` in plain decimal notation.
+/// This is the same range in which JavaScript avoids exponent notation.
+struct Field<'a>(&'a Num, Option<usize>);
+
+impl Display for Field<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let s = jaq_json::write::num(self.0, self.1).to_string();
+        match (self.0, self.1) {
+            (Num::Float(_) | Num::Dec(_), None) => expand_exp(&s).unwrap_or(s).fmt(f),
+            _ => s.fmt(f),
+        }
+    }
+}
+
+/// Convert a number like `-1.5e17` to plain decimal notation.
+///
+/// Return `None` if the number has no exponent or
+/// its magnitude is not in `[1e-6, 1e21)`.
+/// Unlike converting via `f64`, this preserves all digits.
+fn expand_exp(s: &str) -> Option<String> {
+    let (mantissa, exp) = s.split_once(['e', 'E'])?;
+    let exp: i64 = exp.parse().ok()?;
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(m) => ("-", m),
+        None => ("", mantissa.strip_prefix('+').unwrap_or(mantissa)),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = [int, frac].concat();
+    let zeros = digits.len() - digits.trim_start_matches('0').len();
+    let digits = &digits[zeros..];
+    // position of the decimal point relative to the start of `digits`
+    let point = (int.len() - zeros) as i64 + exp;
+    if digits.is_empty() || !(-5..=21).contains(&point) {
+        return None;
+    }
+    let zeros = |n: i64| "0".repeat(n as usize);
+    let len = digits.len() as i64;
+    Some(if point <= 0 {
+        format!("{sign}0.{}{digits}", zeros(-point))
+    } else if point >= len {
+        format!("{sign}{digits}{}", zeros(point - len))
+    } else {
+        let (int, frac) = digits.split_at(point as usize);
+        format!("{sign}{int}.{frac}")
+    })
+}
+
 macro_rules! write_field {
-    ($w:ident, $v:ident, $fs:expr) => {{
+    ($w:ident, $prec:expr, $v:ident, $fs:expr) => {{
         match $v {
             Val::Null => Ok(()),
             Val::TStr(s) => $fs(s),
+            Val::Num(n) => write!($w, "{}", Field(n, $prec)),
             v => write!($w, "{v}"),
         }
     }};
@@ -51,7 +110,7 @@ macro_rules! write_field {
 
 macro_rules! write_row {
     ($w:ident, $v:ident, $delim:expr, $f:expr) => {{
-        let mut iter = $v.0.iter();
+        let mut iter = $v.fields.iter();
 
         if let Some(v) = iter.next() {
             $f(v)?;
@@ -79,17 +138,30 @@ fn write_tsv_str(w: &mut dyn io::Write, b: &[u8]) -> io::Result<()> {
 }
 
 impl Row {
+    /// Write non-integer numbers with given number of decimal places.
+    ///
+    /// This corresponds to [`jaq_json::write::Pp::float_prec`].
+    pub fn with_float_prec(self, float_prec: Option<usize>) -> Self {
+        Self { float_prec, ..self }
+    }
+
     /// Format array value as CSV row.
     pub fn write_csv(&self, w: &mut dyn io::Write) -> io::Result<()> {
-        write_row!(w, self, ',', |v: &Val| write_field!(w, v, |s| {
-            write_csv_str(w, s)
-        }))
+        write_row!(w, self, ',', |v: &Val| write_field!(
+            w,
+            self.float_prec,
+            v,
+            |s| { write_csv_str(w, s) }
+        ))
     }
 
     /// Format array value as TSV row.
     pub fn write_tsv(&self, w: &mut dyn io::Write) -> io::Result<()> {
-        write_row!(w, self, '\t', |v: &Val| write_field!(w, v, |s| {
-            write_tsv_str(w, s)
-        }))
+        write_row!(w, self, '\t', |v: &Val| write_field!(
+            w,
+            self.float_prec,
+            v,
+            |s| { write_tsv_str(w, s) }
+        ))
     }
 }
@@ -160,3 +160,142 @@ fn xml() {
     let xml2: Vec<_> = json_val.iter().map(serialise).collect();
     assert_eq!(xml, xml2.concat());
 }
+
+#[test]
+fn json_float_prec() {
+    let vals = unwrap_collect(read::json::parse_many(
+        b"[1, 1.0, 2.345, 1e2, 18446744073709551616]",
+    ));
+    let pp = |float_prec| write::json::Pp {
+        float_prec,
+        ..Default::default()
+    };
+    let serialise = |pp| {
+        let mut buf = Vec::new();
+        write::json::write(&mut buf, &pp, 0, &vals[0]).unwrap();
+        String::from_utf8(buf).unwrap()
+    };
+    assert_eq!(
+        serialise(pp(None)),
+        "[1,1.0,2.345,1e2,18446744073709551616]"
+    );
+    assert_eq!(serialise(pp(Some(0))), "[1,1,2,100,18446744073709551616]");
+    assert_eq!(
+        serialise(pp(Some(2))),
+        "[1,1.00,2.35,100.00,18446744073709551616]"
+    );
+}
+
+#[test]
+fn csv_float_prec() {
+    let vals = unwrap_collect(read::json::parse_many(b"[1, 0.125, 1e17, 1e21]"));
+    let serialise = |float_prec| {
+        let row = write::tabular::Row::try_from(&vals[0]).ok().unwrap();
+        let mut buf = Vec::new();
+        row.with_float_prec(float_prec).write_csv(&mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    };
+    assert_eq!(serialise(None), "1,0.125,100000000000000000,1e21");
+    assert_eq!(
+        serialise(Some(2)),
+        "1,0.12,100000000000000000.00,1000000000000000000000.00"
+    );
+}
+
+#[test]
+fn json_ascii() {
+    let val = read::json::parse_single("{\"ü\": [\"Grüße 😀\", \"\\n\"]}".as_bytes()).unwrap();
+    let pp = write::json::Pp {
+        ascii: true,
+        ..Default::default()
+    };
+    let mut buf = Vec::new();
+    write::json::write(&mut buf, &pp, 0, &val).unwrap();
+    let out = String::from_utf8(buf).unwrap();
+    assert_eq!(out, r#"{"\u00fc":["Gr\u00fc\u00dfe \ud83d\ude00","\n"]}"#);
+}
+
+#[test]
+fn json_read_many() {
+    let json = "{\"a\": [1, 2]} \"hello\"\n3.5 null [[], {}]";
+    // use a tiny buffer, so that values are split across buffer boundaries
+    let read = std::io::BufReader::with_capacity(3, json.as_bytes());
+    let vals = unwrap_collect(read::json::read_many(read));
+    assert_eq!(
+        vals,
+        unwrap_collect(read::json::parse_many(json.as_bytes()))
+    );
+    assert_eq!(vals.len(), 5);
+}
+
+#[test]
+fn json_read_many_err() {
+    // the offset is the number of bytes read when the error was detected
+    let errs = [
+        ("1 [2, }", "byte offset 7: value expected"),
+        ("[1, 2, 3]]", "byte offset 10: value expected"),
+        ("\"abc", "byte offset 4: unterminated string"),
+    ];
+    for (json, err) in errs {
+        let read = std::io::BufReader::with_capacity(2, json.as_bytes());
+        let mut vals = read::json::read_many(read);
+        assert_eq!(vals.find_map(Result::err).unwrap().to_string(), err);
+    }
+}
+
+#[test]
+fn json_read_many_send() {
+    fn send<T: Send>(_: T) {}
+    send(read::json::read_many("1 2".as_bytes()));
+}
+
+#[test]
+fn json_shared_keys() {
+    use jaq_json::Val;
+    // pointer to the bytes of the first key of an object
+    let key_ptr = |v: &Val| match v {
+        Val::Obj(o) => match o.keys().next() {
+            Some(Val::TStr(s)) => s.as_ptr(),
+            _ => panic!(),
+        },
+        _ => panic!(),
+    };
+
+    // keys are shared inside a value ...
+    let arr = read::json::parse_single(br#"[{"a": 1}, {"a": 2}]"#).unwrap();
+    let Val::Arr(arr) = arr else { panic!() };
+    assert_eq!(key_ptr(&arr[0]), key_ptr(&arr[1]));
+
+    // ... as well as across values
+    let vals = unwrap_collect(read::json::parse_many(br#"{"a": 1} {"a": 2}"#));
+    assert_eq!(key_ptr(&vals[0]), key_ptr(&vals[1]));
+}
+
+#[test]
+fn json_depth() {
+    // parsing and dropping deeply nested values needs more stack
+    // than test threads have by default in debug builds
+    let test = std::thread::Builder::new().stack_size(64 << 20);
+    test.spawn(json_depth_run).unwrap().join().unwrap()
+}
+
+fn json_depth_run() {
+    let nested = |d| "[".repeat(d) + &"]".repeat(d);
+    let depth = jaq_json::read::DEPTH;
+    assert!(read::json::parse_single(nested(depth).as_bytes()).is_ok());
+    for d in [depth + 1, 100_000] {
+        let json = nested(d);
+        let err = read::json::parse_single(json.as_bytes()).unwrap_err();
+        assert!(err.to_string().ends_with("maximal depth exceeded"));
+        let mut vals = read::json::read_many(json.as_bytes());
+        assert!(vals.next().unwrap().is_err());
+    }
+
+    // the limit can be raised
+    let json = nested(depth + 1);
+    assert!(read::json::parse_single_depth(json.as_bytes(), depth + 1).is_ok());
+    let mut vals = read::json::parse_many_depth(json.as_bytes(), depth + 1);
+    assert!(vals.next().unwrap().is_ok());
+    let mut vals = read::json::read_many_depth(json.as_bytes(), depth);
+    assert!(vals.next().unwrap().is_err());
+}
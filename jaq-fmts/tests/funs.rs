@@ -7,6 +7,44 @@ yields!(fromcbor1, "[41] | tobytes | fromcbor", -10);
 yields!(fromcbor2, "[99, 230, 176, 180] | tobytes | fromcbor", "水");
 yields!(tocbor, "-10 | tocbor | . == ([41] | tobytes)", true);
 
+yields!(
+    msgpack_roundtrip,
+    r#"[0, -32, 200, -200, 70000, -70000, 1.5, "a", null, true, {a: [1]}] | . == (tomsgpack | frommsgpack)"#,
+    true
+);
+yields!(
+    msgpack_roundtrip_big,
+    "[18446744073709551615, -9223372036854775808] | . == (tomsgpack | frommsgpack)",
+    true
+);
+yields!(
+    tomsgpack_obj,
+    "{a: 1} | tomsgpack | . == ([129, 161, 97, 1] | tobytes)",
+    true
+);
+yields!(
+    tomsgpack_err,
+    "18446744073709551616 | try tomsgpack catch .",
+    "cannot serialise 18446744073709551616 as MessagePack: integer does not fit into 64 bits: 18446744073709551616"
+);
+yields!(frommsgpack_many, "[1, 2] | tobytes | [frommsgpack]", [1, 2]);
+yields!(
+    frommsgpack_err,
+    "[[146, 1], [212, 1, 0]] | map(tobytes | try frommsgpack catch 0)",
+    [0, 0]
+);
+// deeply nested input yields an error instead of overflowing the stack
+yields!(
+    frommsgpack_depth,
+    "[limit(200000; repeat(145))] + [192] | tobytes | try frommsgpack catch 0",
+    0
+);
+yields!(
+    frommsgpack_depth_ok,
+    "[limit(1000; repeat(145))] + [192] | tobytes | frommsgpack | [paths] | length",
+    1000
+);
+
 // "---" starts a new value, "..." ends a previous value
 yields!(fromyaml_doc1, r#""---\n1" | fromyaml"#, 1);
 yields!(fromyaml_doc2, r#""1\n..." | fromyaml"#, 1);
@@ -28,6 +66,16 @@ yields!(
 );
 yields!(fromyaml_none, r#""" | [fromyaml]"#, json!([]));
 yields!(fromyaml_many, r#""1\n---\n2" | [fromyaml]"#, [1, 2]);
+yields!(
+    fromyaml_block,
+    r#""a:\n  - 1\n  - b: c\n" | fromyaml"#,
+    json!({"a": [1, {"b": "c"}]})
+);
+yields!(
+    fromyaml_alias,
+    r#""a: &x [1]\nb: *x\n" | fromyaml"#,
+    json!({"a": [1], "b": [1]})
+);
 
 yields!(toyaml_nan, "nan | toyaml", ".nan");
 yields!(toyaml_inf, "[infinite,-infinite] | toyaml", "[.inf, -.inf]");
@@ -44,3 +92,84 @@ yields!(
     r"255 | tobytes | tostring | toyaml | explode",
     [-255]
 );
+
+yields!(
+    atcsv,
+    r#"[1, "a\"b", null, true] | @csv"#,
+    r#"1,"a""b",,true"#
+);
+// floats are written without exponent unless they are very large or small
+yields!(
+    atcsv_float,
+    "[1e21, 0.1, 1e17, -1.5e17, 1e-6, 1e-7, 3.0, 0.0] | @csv",
+    "1e21,0.1,100000000000000000,-150000000000000000,0.000001,1e-7,3.0,0.0"
+);
+yields!(
+    attsv_float,
+    "[1e20, 0.1] | @tsv",
+    "100000000000000000000\t0.1"
+);
+yields!(
+    attsv,
+    r#"[1, "a\tb", null, true] | @tsv"#,
+    r#"1	a\tb		true"#
+);
+// format filters are applied to every interpolated value
+yields!(
+    atcsv_interpolated,
+    r#"[1, "a"] | @csv "row: \(.)""#,
+    r#"row: 1,"a""#
+);
+yields!(
+    atformats_interpolated,
+    r#""<&>" | [@json "\(.)", @text "\(.)", @html "\(.)", @uri "\(.)", @base64 "\(.)"]"#,
+    ["\"<&>\"", "<&>", "&lt;&amp;&gt;", "%3C%26%3E", "PCY+"]
+);
+
+yields!(
+    tocsv_row_err,
+    r#"{a: 1} | try tocsv catch ."#,
+    r#"cannot serialise {"a":1} as CSV: expected table row (array), found {"a":1}"#
+);
+yields!(
+    totsv_field_err,
+    r#"[1, [2]] | try totsv catch ."#,
+    "cannot serialise [1,[2]] as TSV: expected table field (null, bool, number, or string), found [2]"
+);
+
+yields!(
+    fromcsv_sep,
+    r#""1;\"a;b\"\n\"c\nd\";" | [fromcsv(";")]"#,
+    json!([[1, "a;b"], ["c\nd", null]])
+);
+yields!(
+    fromcsv_sep_invalid,
+    r#"["", ";;", "\""] | map(. as $sep | "" | try fromcsv($sep) catch 0)"#,
+    [0, 0, 0]
+);
+yields!(
+    fromcsv_sep_long,
+    r#""" | try fromcsv(";" * 1000) catch ."#,
+    "invalid CSV separator: \";;;;;;;;;;;;;;;;;;;;;;;;;;;;;..."
+);
+yields!(
+    fromcsv_header,
+    r#""a,b\n1,2\n3\n4,5,6" | [fromcsv(","; true)]"#,
+    json!([{"a": 1, "b": 2}, {"a": 3, "b": null}, {"a": 4, "b": 5}])
+);
+yields!(
+    fromcsv_no_header,
+    r#""a;b\n1;2" | [fromcsv(";"; false)]"#,
+    json!([["a", "b"], [1, 2]])
+);
+
+yields!(
+    totoml_root_err,
+    "1 | try totoml catch .",
+    "cannot serialise 1 as TOML: TOML root must be an object, found: 1"
+);
+yields!(
+    totoml_null_err,
+    "{a: [1, null]} | try totoml catch .",
+    r#"cannot serialise {"a":[1,null]} as TOML: could not encode null as TOML value"#
+);
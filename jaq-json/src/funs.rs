@@ -21,7 +21,7 @@ impl Val {
             Val::BStr(b) => Ok(Val::from(b.len())),
             Val::Arr(a) => Ok(Val::from(a.len())),
             Val::Obj(o) => Ok(Val::from(o.len())),
-            Val::Bool(_) => Err(Error::str(format_args!("{self} has no length"))),
+            Val::Bool(_) => Err(Error::with_val("", self.clone(), " has no length")),
         }
     }
 
@@ -42,12 +42,15 @@ impl Val {
                 Ok(Box::new(iw.filter_map(|(i, w)| (w == **y).then_some(i))))
             }
             (Val::Arr(x), Val::Arr(y)) => {
+                let eq = |w: &[Val]| w.iter().zip(y.iter()).all(|(w, y)| w.eq_nan(y));
                 let iw = x.windows(y.len()).enumerate();
-                Ok(Box::new(iw.filter_map(|(i, w)| (w == **y).then_some(i))))
+                Ok(Box::new(iw.filter_map(move |(i, w)| eq(w).then_some(i))))
             }
             (Val::Arr(x), y) => {
                 let ix = x.iter().enumerate();
-                Ok(Box::new(ix.filter_map(move |(i, x)| (x == y).then_some(i))))
+                Ok(Box::new(
+                    ix.filter_map(move |(i, x)| x.eq_nan(y).then_some(i)),
+                ))
             }
             (x, y) => Err(Error::index(x.clone(), y.clone())),
         }
@@ -66,10 +69,28 @@ impl Val {
             (Self::Obj(l), Self::Obj(r)) => r
                 .iter()
                 .all(|(k, r)| l.get(k).is_some_and(|l| l.contains(r))),
-            _ => self == other,
+            _ => self.eq_nan(other),
         }
     }
 
+    /// `a` has `k` iff `a[k]` points to data.
+    ///
+    /// Unlike `a.index_opt(k)`, this does not construct `a[k]`;
+    /// for example, for an array `a`, this only checks whether `k` is in bounds.
+    fn has(&self, k: &Self) -> ValR {
+        Ok(match (self, k) {
+            (Self::Arr(a), Self::Num(i)) if i.is_int() => {
+                let i = i.as_pos_usize();
+                i.and_then(|i| crate::abs_index(i, a.len())).is_some()
+            }
+            // `a[k]` yields the indices of `k` in `a`, which always exist
+            (Self::Arr(_), Self::Arr(_)) => true,
+            (Self::Obj(o), k) => o.contains_key(k),
+            _ => self.clone().index_opt(k)?.is_some(),
+        }
+        .into())
+    }
+
     fn to_bytes(&self) -> Result<Bytes, Self> {
         match self {
             Val::Num(n) => n
@@ -118,8 +139,8 @@ fn bmme<'a>(iter: BoxIter<'a, ValR>) -> BoxIter<'a, ValX<'a>> {
     Box::new(iter.map(|r| r.map_err(Exn::from)))
 }
 
-fn parse_fail(i: &impl fmt::Display, fmt: &str, e: impl fmt::Display) -> Error {
-    Error::str(format_args!("cannot parse {i} as {fmt}: {e}"))
+fn parse_fail(i: &Val, fmt: &str, e: impl fmt::Display) -> Error {
+    Error::with_val("cannot parse ", i.clone(), alloc::format!(" as {fmt}: {e}"))
 }
 
 self_cell::self_cell!(
@@ -185,17 +206,28 @@ fn base<D: for<'a> DataT<V<'a> = Val>>() -> Box<[Filter<RunPtr<D>>]> {
             }))
         }),
         ("tojson", v(0), |cv| bome(Ok(Val::utf8_str(cv.1.to_json())))),
+        ("tojson", v(1), |cv| {
+            unary(cv, |v, n| {
+                let fail = || Error::with_val("cannot indent by ", n.clone(), "");
+                let n = n.as_isize().and_then(|n| usize::try_from(n).ok());
+                let n = n.ok_or_else(fail)?;
+                let pp = crate::write::Pp {
+                    indent: (n > 0).then(|| " ".repeat(n)),
+                    sep_space: n > 0,
+                    ..Default::default()
+                };
+                Ok(Val::utf8_str(v.to_json_with(&pp)))
+            })
+        }),
         ("tobytes", v(0), |cv| {
-            let fail = |v| Error::str(format_args!("cannot convert {v} to bytes"));
+            let fail = |v| Error::with_val("cannot convert ", v, " to bytes");
             bome(cv.1.to_bytes().map(Val::byte_str).map_err(fail))
         }),
         ("length", v(0), |cv| bome(cv.1.length())),
         ("contains", v(1), |cv| {
             unary(cv, |x, y| Ok(Val::from(x.contains(&y))))
         }),
-        ("has", v(1), |cv| {
-            unary(cv, |v, k| v.index_opt(&k).map(|o| o.is_some().into()))
-        }),
+        ("has", v(1), |cv| unary(cv, |v, k| v.has(&k))),
         ("indices", v(1), |cv| {
             let to_int = |i: usize| Val::from(i as isize);
             unary(cv, move |x, v| {
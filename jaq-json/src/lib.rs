@@ -3,6 +3,15 @@
 //! This crate provides a few macros for formatting / writing;
 //! this is done in order to function with both
 //! [`core::fmt::Write`] and [`std::io::Write`].
+//!
+//! When the `serde` feature is enabled, [`Val`] implements
+//! `Serialize` and `Deserialize`.
+//! This allows processing values of other types, such as `serde_json::Value`,
+//! by converting them with `serde_json::from_value` and `serde_json::to_value`.
+//! Serialization is lossy for numbers:
+//! integers that do not fit into 64 bits as well as decimals
+//! are serialized as floating-point numbers, and there is no way to opt out.
+//! Text strings that are not valid UTF-8 fail to serialize.
 #![no_std]
 #![forbid(unsafe_code)]
 #![warn(missing_docs)]
@@ -73,6 +82,36 @@ fn val_send_sync() {
     send_sync(Val::default())
 }
 
+#[cfg(all(feature = "sync", feature = "std"))]
+#[test]
+fn par_run() {
+    use jaq_core::load::{Arena, File, Loader};
+    let arena = Arena::default();
+    let loader = Loader::new(jaq_core::defs());
+    let code = ".[] | ., . * 10";
+    let modules = loader.load(&arena, File { path: (), code }).unwrap();
+    let filter = jaq_core::Compiler::default()
+        .with_funs(jaq_core::funs())
+        .compile(modules)
+        .unwrap();
+
+    let int = |i| Val::from(i as isize);
+    let inputs = (0..100).map(|i| Val::from_iter([int(i), int(-i)]));
+    let expected: Vec<_> = (0..100)
+        .flat_map(|i| [i, i * 10, -i, -i * 10])
+        .map(|i| Ok(int(i)))
+        .collect();
+    for threads in [0, 1, 3, 8, 200] {
+        for batch in [0, 1, 7, 100, 1000] {
+            let outs: Vec<_> = filter.par_run(inputs.clone(), threads, batch).collect();
+            assert_eq!(outs, expected);
+        }
+    }
+    // inputs are taken lazily
+    let mut outs = filter.par_run((0..).map(|i| Val::from_iter([int(i)])), 4, 16);
+    assert_eq!(outs.nth(1001), Some(Ok(int(500 * 10))));
+}
+
 #[cfg(target_arch = "x86_64")]
 const _: () = {
     assert!(core::mem::size_of::<Val>() == 16);
@@ -130,6 +169,39 @@ fn rc_unwrap_or_clone<T: Clone>(a: Rc<T>) -> T {
     Rc::try_unwrap(a).unwrap_or_else(|a| (*a).clone())
 }
 
+/// Iterate over the elements of an array, cloning them only if the array is shared.
+///
+/// Unlike `rc_unwrap_or_clone(a).into_iter()`,
+/// this does not clone the whole array upfront if it is shared,
+/// which makes it cheap to consume only few elements, such as in `first(.[])`.
+fn arr_iter(a: Rc<Vec<Val>>) -> impl Iterator<Item = Val> {
+    let (owned, shared) = match Rc::try_unwrap(a) {
+        Ok(a) => (Some(a.into_iter()), None),
+        Err(a) => (None, Some((0..).map_while(move |i| a.get(i).cloned()))),
+    };
+    owned
+        .into_iter()
+        .flatten()
+        .chain(shared.into_iter().flatten())
+}
+
+/// Iterate over the entries of an object, cloning them only if the object is shared.
+///
+/// See [`arr_iter`].
+fn obj_iter(o: Rc<Map>) -> impl Iterator<Item = (Val, Val)> {
+    let (owned, shared) = match Rc::try_unwrap(o) {
+        Ok(o) => (Some(o.into_iter()), None),
+        Err(o) => {
+            let get = move |i| o.get_index(i).map(|(k, v)| (k.clone(), v.clone()));
+            (None, Some((0..).map_while(get)))
+        }
+    };
+    owned
+        .into_iter()
+        .flatten()
+        .chain(shared.into_iter().flatten())
+}
+
 impl jaq_core::ValT for Val {
     fn from_num(n: &str) -> ValR {
         Ok(Self::Num(Num::from_str(n)))
@@ -142,16 +214,16 @@ impl jaq_core::ValT for Val {
     fn key_values(self) -> Box<dyn Iterator<Item = Result<(Val, Val), Error>>> {
         let arr_idx = |(i, x)| Ok((Self::from(i as isize), x));
         match self {
-            Self::Arr(a) => Box::new(rc_unwrap_or_clone(a).into_iter().enumerate().map(arr_idx)),
-            Self::Obj(o) => Box::new(rc_unwrap_or_clone(o).into_iter().map(Ok)),
+            Self::Arr(a) => Box::new(arr_iter(a).enumerate().map(arr_idx)),
+            Self::Obj(o) => Box::new(obj_iter(o).map(Ok)),
             _ => box_once(Err(Error::typ(self, Type::Iter.as_str()))),
         }
     }
 
     fn values(self) -> Box<dyn Iterator<Item = ValR>> {
         match self {
-            Self::Arr(a) => Box::new(rc_unwrap_or_clone(a).into_iter().map(Ok)),
-            Self::Obj(o) => Box::new(rc_unwrap_or_clone(o).into_iter().map(|(_k, v)| Ok(v))),
+            Self::Arr(a) => Box::new(arr_iter(a).map(Ok)),
+            Self::Obj(o) => Box::new(obj_iter(o).map(|(_k, v)| Ok(v))),
             _ => box_once(Err(Error::typ(self, Type::Iter.as_str()))),
         }
     }
@@ -296,6 +368,20 @@ impl jaq_core::ValT for Val {
             _ => Self::utf8_str(self.to_json()),
         }
     }
+
+    /// Like in jq, NaN is smaller than any number, including itself.
+    fn cmp_op(&self, op: jaq_core::ops::Cmp, other: &Self) -> bool {
+        use jaq_core::ops::Cmp;
+        let o = self.cmp_nan(other);
+        match op {
+            Cmp::Lt => o.is_lt(),
+            Cmp::Le => o.is_le(),
+            Cmp::Gt => o.is_gt(),
+            Cmp::Ge => o.is_ge(),
+            Cmp::Eq => o.is_eq(),
+            Cmp::Ne => o.is_ne(),
+        }
+    }
 }
 
 impl jaq_std::ValT for Val {
@@ -474,8 +560,12 @@ impl Val {
     }
 
     fn to_json(&self) -> Vec<u8> {
+        self.to_json_with(&write::Pp::default())
+    }
+
+    fn to_json_with(&self, pp: &write::Pp) -> Vec<u8> {
         let mut buf = write::Buf(Vec::new());
-        write::write_buf(&mut buf, &write::Pp::default(), 0, self).unwrap();
+        write::write_buf(&mut buf, pp, 0, self).unwrap();
         buf.0
     }
 
@@ -594,7 +684,8 @@ impl core::ops::Sub for Val {
             (Self::Num(x), Self::Num(y)) => Ok(Self::Num(x - y)),
             (Self::Arr(mut l), Self::Arr(r)) => {
                 let r = r.iter().collect::<alloc::collections::BTreeSet<_>>();
-                Rc::make_mut(&mut l).retain(|x| !r.contains(x));
+                // values that contain NaN are never removed, like in jq
+                Rc::make_mut(&mut l).retain(|x| !r.get(x).is_some_and(|y| x.eq_nan(y)));
                 Ok(Self::Arr(l))
             }
             (l, r) => Err(Error::math(l, ops::Math::Sub, r)),
@@ -702,13 +793,34 @@ impl PartialOrd for Val {
 
 impl Ord for Val {
     fn cmp(&self, other: &Self) -> Ordering {
+        self.cmp_by(other, Num::cmp)
+    }
+}
+
+impl Val {
+    /// Compare two values like jq's comparison operators.
+    ///
+    /// Unlike [`Ord`], NaN is smaller than any number, including itself.
+    fn cmp_nan(&self, other: &Self) -> Ordering {
+        self.cmp_by(other, Num::cmp_nan)
+    }
+
+    /// Equality like jq's `==`, where NaN does not equal itself.
+    ///
+    /// This is used by filters that compare values, such as `contains` and `indices`.
+    pub(crate) fn eq_nan(&self, other: &Self) -> bool {
+        self.cmp_nan(other).is_eq()
+    }
+
+    /// Compare two values, comparing numbers with `num`.
+    fn cmp_by(&self, other: &Self, num: fn(&Num, &Num) -> Ordering) -> Ordering {
         use Ordering::{Equal, Greater, Less};
         match (self, other) {
             (Self::Null, Self::Null) => Equal,
             (Self::Bool(x), Self::Bool(y)) => x.cmp(y),
-            (Self::Num(x), Self::Num(y)) => x.cmp(y),
+            (Self::Num(x), Self::Num(y)) => num(x, y),
             (Self::BStr(x) | Self::TStr(x), Self::BStr(y) | Self::TStr(y)) => x.cmp(y),
-            (Self::Arr(x), Self::Arr(y)) => x.cmp(y),
+            (Self::Arr(x), Self::Arr(y)) => cmp_iter(x.iter(), y.iter(), num),
             (Self::Obj(x), Self::Obj(y)) => match (x.len(), y.len()) {
                 (0, 0) => Equal,
                 (0, _) => Less,
@@ -719,11 +831,11 @@ impl Ord for Val {
                     l.sort_by_key(|(k, _v)| *k);
                     r.sort_by_key(|(k, _v)| *k);
                     // TODO: make this nicer
-                    let kl = l.iter().map(|(k, _v)| k);
-                    let kr = r.iter().map(|(k, _v)| k);
-                    let vl = l.iter().map(|(_k, v)| v);
-                    let vr = r.iter().map(|(_k, v)| v);
-                    kl.cmp(kr).then_with(|| vl.cmp(vr))
+                    let kl = l.iter().map(|(k, _v)| *k);
+                    let kr = r.iter().map(|(k, _v)| *k);
+                    let vl = l.iter().map(|(_k, v)| *v);
+                    let vr = r.iter().map(|(_k, v)| *v);
+                    cmp_iter(kl, kr, num).then_with(|| cmp_iter(vl, vr, num))
                 }
             },
 
@@ -745,6 +857,26 @@ impl Ord for Val {
     }
 }
 
+/// Lexicographically compare two sequences of values.
+fn cmp_iter<'a>(
+    l: impl Iterator<Item = &'a Val>,
+    mut r: impl Iterator<Item = &'a Val>,
+    num: fn(&Num, &Num) -> Ordering,
+) -> Ordering {
+    for x in l {
+        match r.next().map(|y| x.cmp_by(y, num)) {
+            Some(Ordering::Equal) => (),
+            Some(o) => return o,
+            None => return Ordering::Greater,
+        }
+    }
+    if r.next().is_some() {
+        Ordering::Less
+    } else {
+        Ordering::Equal
+    }
+}
+
 impl PartialEq for Val {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
@@ -352,6 +352,22 @@ impl PartialOrd for Num {
     }
 }
 
+impl Num {
+    /// Compare numbers like jq's comparison operators.
+    ///
+    /// Unlike [`Ord`], NaN is smaller than any number, including itself.
+    pub(crate) fn cmp_nan(&self, other: &Self) -> Ordering {
+        match self {
+            Self::Float(f) if f.is_nan() => Ordering::Less,
+            _ => self.cmp(other),
+        }
+    }
+}
+
+/// Total order of numbers, used for sorting.
+///
+/// NaN is smaller than any other number and equal to itself,
+/// so sorting yields NaNs first, regardless of their position in the input.
 impl Ord for Num {
     fn cmp(&self, other: &Self) -> Ordering {
         match (self, other) {
@@ -380,14 +396,12 @@ fn float_cmp(left: f64, right: f64) -> Ordering {
     if left == 0. && right == 0. {
         // consider negative and positive 0 as equal
         Ordering::Equal
-    } else if left.is_nan() {
+    } else if left.is_nan() || right.is_nan() {
         // there are more than 50 shades of NaN, and which of these
         // you strike when you perform a calculation is not deterministic (!),
         // therefore `total_cmp` may yield different results for the same calculation
         // so we bite the bullet and handle this like in jq
-        Ordering::Less
-    } else if right.is_nan() {
-        Ordering::Greater
+        right.is_nan().cmp(&left.is_nan())
     } else {
         f64::total_cmp(&left, &right)
     }
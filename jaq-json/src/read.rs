@@ -1,6 +1,7 @@
 //! JSON support.
 use crate::{Map, Num, Val};
 use alloc::{string::ToString, vec::Vec};
+use bytes::Bytes;
 use core::fmt::{self, Formatter};
 use hifijson::token::{Expect, Lex};
 use hifijson::{LexAlloc, SliceLexer};
@@ -18,6 +19,16 @@ fn ws_tk<L: Lex>(lexer: &mut L) -> Option<u8> {
     }
 }
 
+/// Default maximal nesting depth of arrays and objects when parsing.
+///
+/// This is the same limit as in jq.
+/// Parsing is recursive, so deeply nested input could overflow the stack.
+/// This limit only protects parsing; other operations on values,
+/// such as printing, updating, or dropping them, are recursive as well.
+/// To parse with a different limit, use the `*_depth` variants of
+/// the parsing functions, such as [`parse_many_depth`].
+pub const DEPTH: usize = 10000;
+
 /// Parse error.
 #[derive(Debug)]
 pub struct Error(usize, hifijson::Error);
@@ -33,29 +44,71 @@ impl std::error::Error for Error {}
 
 /// Parse exactly one JSON value.
 pub fn parse_single(slice: &[u8]) -> Result<Val, Error> {
+    parse_single_depth(slice, DEPTH)
+}
+
+/// Parse exactly one JSON value, nesting arrays and objects at most `depth` deep.
+pub fn parse_single_depth(slice: &[u8], depth: usize) -> Result<Val, Error> {
     let offset = |rest: &[u8]| rest.as_ptr() as usize - slice.as_ptr() as usize;
     let mut lexer = SliceLexer::new(slice);
+    let mut keys = Keys::default();
     lexer
-        .exactly_one(ws_tk, parse)
+        .exactly_one(ws_tk, |next, lexer| parse(next, lexer, &mut keys, depth))
         .map_err(|e| Error(offset(lexer.as_slice()), e))
 }
 
 /// Parse a sequence of JSON values.
 pub fn parse_many(slice: &[u8]) -> impl Iterator<Item = Result<Val, Error>> + '_ {
+    parse_many_depth(slice, DEPTH)
+}
+
+/// Parse a sequence of JSON values, nesting arrays and objects at most `depth` deep.
+pub fn parse_many_depth(
+    slice: &[u8],
+    depth: usize,
+) -> impl Iterator<Item = Result<Val, Error>> + '_ {
     let offset = |rest: &[u8]| rest.as_ptr() as usize - slice.as_ptr() as usize;
     let mut lexer = SliceLexer::new(slice);
+    let mut keys = Keys::default();
     core::iter::from_fn(move || {
-        Some(parse(ws_tk(&mut lexer)?, &mut lexer).map_err(|e| Error(offset(lexer.as_slice()), e)))
+        let v = parse(ws_tk(&mut lexer)?, &mut lexer, &mut keys, depth);
+        Some(v.map_err(|e| Error(offset(lexer.as_slice()), e)))
     })
 }
 
 #[cfg(feature = "std")]
 /// Read a sequence of JSON values.
+///
+/// Values are parsed incrementally, so
+/// only the value that is currently parsed is held in memory.
+/// Parse errors are returned as [`io::ErrorKind::InvalidData`],
+/// wrapping an [`Error`] that contains
+/// the number of bytes read when the error was detected.
 pub fn read_many<'a>(read: impl io::BufRead + 'a) -> impl Iterator<Item = io::Result<Val>> + 'a {
-    let invalid_data = |e| io::Error::new(io::ErrorKind::InvalidData, e);
-    let mut lexer = hifijson::IterLexer::new(read.bytes());
+    read_many_depth(read, DEPTH)
+}
+
+#[cfg(feature = "std")]
+/// Read a sequence of JSON values, nesting arrays and objects at most `depth` deep.
+pub fn read_many_depth<'a>(
+    read: impl io::BufRead + 'a,
+    depth: usize,
+) -> impl Iterator<Item = io::Result<Val>> + 'a {
+    // number of bytes that the lexer has consumed so far
+    // (this is atomic, so that the returned iterator is `Send` if `read` is)
+    use core::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+    let pos = std::sync::Arc::new(AtomicUsize::new(0));
+    let pos_ = pos.clone();
+    let bytes = read.bytes().inspect(move |_| {
+        pos_.fetch_add(1, Relaxed);
+    });
+    let invalid_data =
+        move |e| io::Error::new(io::ErrorKind::InvalidData, Error(pos.load(Relaxed), e));
+    let mut lexer = hifijson::IterLexer::new(bytes);
+    let mut keys = Keys::default();
     core::iter::from_fn(move || {
-        let v = ws_tk(&mut lexer).map(|next| parse(next, &mut lexer).map_err(invalid_data));
+        let v = ws_tk(&mut lexer).map(|next| parse(next, &mut lexer, &mut keys, depth));
+        let v = v.map(|v| v.map_err(&invalid_data));
         // always return I/O error if present, regardless of the output value!
         lexer.error.take().map(Err).or(v)
     })
@@ -75,6 +128,29 @@ pub fn parse_single_num(slice: &[u8]) -> Option<Num> {
     })
 }
 
+/// Cache of object keys, so that equal keys share the same memory.
+///
+/// This saves memory when parsing many objects with the same keys,
+/// such as records in an array or in a stream of values.
+#[derive(Default)]
+struct Keys(indexmap::IndexSet<Bytes, foldhash::fast::RandomState>);
+
+impl Keys {
+    /// Maximal number of cached keys, bounding the memory used by the cache.
+    const MAX: usize = 1024;
+
+    fn get(&mut self, key: Vec<u8>) -> Bytes {
+        if let Some(key) = self.0.get(&*key) {
+            return key.clone();
+        }
+        let key = Bytes::from(key);
+        if self.0.len() < Self::MAX {
+            self.0.insert(key.clone());
+        }
+        key
+    }
+}
+
 /// Parse a JSON string as byte or text string, preserving invalid UTF-8 as-is.
 fn parse_string<L: LexAlloc>(lexer: &mut L, bytes: bool) -> Result<Vec<u8>, hifijson::Error> {
     let on_string = |bytes: &mut L::Bytes, out: &mut Vec<u8>| {
@@ -116,7 +192,14 @@ fn parse_num<L: LexAlloc>(lexer: &mut L) -> Result<Num, hifijson::Error> {
 /// If the underlying lexer reads input fallibly (for example [`hifijson::IterLexer`]),
 /// the error returned by this function might be misleading.
 /// In that case, always check whether the lexer contains an error.
-fn parse<L: LexAlloc>(next: u8, lexer: &mut L) -> Result<Val, hifijson::Error> {
+/// If the value contains arrays or objects nested deeper than `depth`, fail.
+fn parse<L: LexAlloc>(
+    next: u8,
+    lexer: &mut L,
+    keys: &mut Keys,
+    depth: usize,
+) -> Result<Val, hifijson::Error> {
+    let deeper = || depth.checked_sub(1).ok_or(hifijson::Error::Depth);
     Ok(match next {
         b'n' if lexer.strip_prefix(b"null") => Val::Null,
         b't' if lexer.strip_prefix(b"true") => Val::Bool(true),
@@ -127,19 +210,24 @@ fn parse<L: LexAlloc>(next: u8, lexer: &mut L) -> Result<Val, hifijson::Error> {
         b'0'..=b'9' | b'+' | b'-' => Val::Num(parse_num(lexer)?),
         b'"' => Val::utf8_str(parse_string(lexer.discarded(), false)?),
         b'[' => Val::Arr({
+            let depth = deeper()?;
             let mut arr = Vec::new();
             lexer.discarded().seq(b']', ws_tk, |next, lexer| {
-                arr.push(parse(next, lexer)?);
+                arr.push(parse(next, lexer, keys, depth)?);
                 Ok::<_, hifijson::Error>(())
             })?;
             arr.into()
         }),
         b'{' => Val::obj({
+            let depth = deeper()?;
             let mut obj = Map::default();
             lexer.discarded().seq(b'}', ws_tk, |next, lexer| {
-                let key = parse(next, lexer)?;
+                let key = match next {
+                    b'"' => Val::TStr(keys.get(parse_string(lexer.discarded(), false)?).into()),
+                    _ => parse(next, lexer, keys, depth)?,
+                };
                 lexer.expect(ws_tk, b':').ok_or(Expect::Colon)?;
-                let value = parse(ws_tk(lexer).ok_or(Expect::Value)?, lexer)?;
+                let value = parse(ws_tk(lexer).ok_or(Expect::Value)?, lexer, keys, depth)?;
                 obj.insert(key, value);
                 Ok::<_, hifijson::Error>(())
             })?;
@@ -1,7 +1,8 @@
 use crate::{Num, Val};
 use alloc::{fmt, string::String};
+use num_traits::cast::ToPrimitive;
 use serde_core::de::{Error, MapAccess, SeqAccess, Visitor};
-use serde_core::{Deserialize, Deserializer};
+use serde_core::{Deserialize, Deserializer, Serialize, Serializer};
 
 struct ValueVisitor;
 
@@ -83,3 +84,54 @@ impl<'de> Deserialize<'de> for Val {
         deserializer.deserialize_any(ValueVisitor)
     }
 }
+
+/// Serialize a value.
+///
+/// Text strings that are not valid UTF-8 yield an error.
+impl Serialize for Val {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde_core::ser::Error;
+        match self {
+            Self::Null => serializer.serialize_unit(),
+            Self::Bool(b) => serializer.serialize_bool(*b),
+            Self::Num(n) => n.serialize(serializer),
+            Self::TStr(s) => match core::str::from_utf8(s) {
+                Ok(s) => serializer.serialize_str(s),
+                Err(e) => Err(S::Error::custom(format_args!("invalid text string: {e}"))),
+            },
+            Self::BStr(b) => serializer.serialize_bytes(b),
+            Self::Arr(a) => serializer.collect_seq(a.iter()),
+            Self::Obj(o) => serializer.collect_map(o.iter()),
+        }
+    }
+}
+
+/// Serialize a number.
+///
+/// Integers that do not fit into 64 bits as well as
+/// decimals are serialized as floating-point numbers.
+impl Serialize for Num {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Int(i) => serializer.serialize_i64(*i as i64),
+            Self::BigInt(i) => match (i.to_i64(), i.to_u64()) {
+                (Some(i), _) => serializer.serialize_i64(i),
+                (_, Some(u)) => serializer.serialize_u64(u),
+                _ => serializer.serialize_f64(self.as_f64()),
+            },
+            Self::Float(f) => serializer.serialize_f64(*f),
+            Self::Dec(_) => serializer.serialize_f64(self.as_f64()),
+        }
+    }
+}
+
+#[test]
+fn serialize() {
+    use serde_json::{json, to_value};
+    let v = json!({"a": [1, -2, 3.5, "b", null, true]});
+    assert_eq!(
+        to_value(serde_json::from_value::<Val>(v.clone()).unwrap()).unwrap(),
+        v
+    );
+    assert!(to_value(Val::utf8_str(b"a\xffb".to_vec())).is_err());
+}
@@ -4,7 +4,7 @@
 //! formatters ([core::fmt::Formatter]) and
 //! writers ([std::io::Write]) from the same code.
 
-use crate::Val;
+use crate::{Num, Val};
 use alloc::string::String;
 use core::fmt::{self, Formatter};
 #[cfg(feature = "std")]
@@ -172,6 +172,55 @@ pub struct Pp<S = String> {
     /// {1:2}  as {"1:2": null}, whereas it interprets
     /// {1: 2} as {1: 2}.
     pub sep_space: bool,
+    /// write non-integer numbers with given number of decimal places
+    ///
+    /// If this is `None`, then floating-point numbers are written with
+    /// the shortest representation that parses back to the same number, and
+    /// decimal numbers are written as they were read.
+    pub float_prec: Option<usize>,
+    /// escape all non-ASCII characters in strings with `\uXXXX`
+    pub ascii: bool,
+}
+
+/// Display a UTF-8 string with all non-ASCII characters escaped as `\uXXXX`.
+///
+/// Characters outside the Basic Multilingual Plane are escaped as UTF-16 surrogate pairs, and
+/// invalid UTF-8 sequences are escaped as the Unicode replacement character.
+pub fn ascii(s: &[u8]) -> impl fmt::Display + '_ {
+    struct Ascii<'a>(&'a [u8]);
+    impl fmt::Display for Ascii<'_> {
+        fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+            use bstr::ByteSlice;
+            use fmt::Write;
+            for c in self.0.chars() {
+                if c.is_ascii() {
+                    f.write_char(c)?
+                } else {
+                    for u in c.encode_utf16(&mut [0; 2]) {
+                        write!(f, "\\u{u:04x}")?
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+    Ascii(s)
+}
+
+/// Display a number, using `prec` decimal places for finite non-integers if given.
+pub fn num(n: &Num, prec: Option<usize>) -> impl fmt::Display + '_ {
+    struct Prec<'a>(&'a Num, Option<usize>);
+    impl fmt::Display for Prec<'_> {
+        fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+            match (self.0, self.1) {
+                (Num::Float(_) | Num::Dec(_), Some(prec)) if self.0.as_f64().is_finite() => {
+                    write!(f, "{:.prec$}", self.0.as_f64())
+                }
+                (n, _) => n.fmt(f),
+            }
+        }
+    }
+    Prec(n, prec)
 }
 
 /// Apply a style `$style` to the output of `$f`.
@@ -210,8 +259,12 @@ macro_rules! format_val {
             Val::Null => color!(null, write!($w, "null")),
             Val::Bool(true) => color!(r#true, write!($w, "true")),
             Val::Bool(false) => color!(r#false, write!($w, "false")),
-            Val::Num(n) => color!(num, write!($w, "{n}")),
+            Val::Num(n) => color!(num, write!($w, "{}", $crate::write::num(n, $pp.float_prec))),
             Val::BStr(b) => color!(bstr, $crate::write_bytes!($w, b)),
+            Val::TStr(s) if $pp.ascii => color!(
+                str,
+                $crate::write_utf8!($w, s, |part| write!($w, "{}", $crate::write::ascii(part)))
+            ),
             Val::TStr(s) => color!(
                 str,
                 $crate::write_utf8!($w, s, |part| write!($w, "{}", $crate::bstr(part)))
@@ -265,7 +318,9 @@ macro_rules! write_val {
     ($w:ident, $pp:ident, $level:expr, $v:ident, $f:expr) => {{
         use $crate::Val::TStr;
         match $v {
-            TStr(s) => style!($w, $pp, str, write_utf8!($w, s, |part| $w.write_all(part))),
+            TStr(s) if !$pp.ascii => {
+                style!($w, $pp, str, write_utf8!($w, s, |part| $w.write_all(part)))
+            }
             _ => format_val!($w, $pp, $level, $v, $f),
         }
     }};
@@ -303,6 +358,26 @@ pub fn write(w: &mut dyn io::Write, pp: &Pp, level: usize, v: &Val) -> io::Resul
     write_val!(w, pp, level, v, write)
 }
 
+/// Write a value as compact JSON, like `tojson`.
+///
+/// Unlike `tojson`, this writes directly to `w` instead of
+/// constructing a string, so it is suitable for very large values.
+/// Wrap `w` in a [`io::BufWriter`] if it is not buffered already.
+/// Together with [`crate::read::read_many`], this allows for transforming
+/// streams of values without holding their serialisations in memory.
+///
+/// ~~~
+/// use jaq_json::{read::parse_single, write::write_json};
+/// let v = parse_single(br#"{"a": [1, "b", null]}"#).unwrap();
+/// let mut out = Vec::new();
+/// write_json(&mut out, &v).unwrap();
+/// assert_eq!(out, br#"{"a":[1,"b",null]}"#);
+/// ~~~
+#[cfg(feature = "std")]
+pub fn write_json(w: &mut dyn io::Write, v: &Val) -> io::Result<()> {
+    write(w, &Pp::default(), 0, v)
+}
+
 pub(crate) struct Buf(pub(crate) alloc::vec::Vec<u8>);
 
 impl Buf {
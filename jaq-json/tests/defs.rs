@@ -22,6 +22,27 @@ fn entries() {
     give(json!([]), "from_entries", json!({}));
 }
 
+yields!(in_arr, "[1, 2 | in([0, 1])]", [true, false]);
+yields!(
+    in_obj,
+    r#"["a", "c" | in({"a": 1, "b": null})]"#,
+    [true, false]
+);
+
+yields!(
+    haspath_nested,
+    r#"{"a": [{"b": null}]} | [haspath(["a", 0, "b"], ["a", 0, "c"], ["a", 1], ["b"])]"#,
+    [true, false, false, false]
+);
+yields!(haspath_empty, "1 | haspath([])", true);
+yields!(haspath_null, r#"{"a": null} | haspath(["a", "b"])"#, false);
+yields!(haspath_neg, "[[1, 2]] | haspath([-1, -2])", true);
+yields!(
+    haspath_err,
+    r#"{"a": 1} | try haspath(["a", "b"]) catch -1"#,
+    -1
+);
+
 #[test]
 fn inside() {
     give(
@@ -42,6 +63,17 @@ fn inside() {
     give(json!({"foo": 12, "bar": [{"barp": 15}]}), f, json!(false));
 }
 
+yields!(
+    index_none,
+    r#""abc" | [index("d"), rindex("d")]"#,
+    json!([null, null])
+);
+yields!(
+    index_arr,
+    "[0, 1, 2, 1, 2] | [index([1, 2]), rindex([1, 2])]",
+    [1, 3]
+);
+
 yields!(
     keys,
     r#"{"foo":null,"abc":null,"fax":null,"az":null} | keys"#,
@@ -63,6 +95,106 @@ yields!(
     json!([["a"], ["b", 0], ["b", 1]])
 );
 
+yields!(
+    pick_obj,
+    "{a: {b: 1, c: 2}, d: 3} | pick(.a.c, .d)",
+    json!({"a": {"c": 2}, "d": 3})
+);
+yields!(
+    pick_missing,
+    "{a: 1} | pick(.b.c)",
+    json!({"b": {"c": null}})
+);
+yields!(pick_empty, "{a: 1} | pick(empty)", json!(null));
+yields!(
+    pick_arr,
+    "[1, 2, 3] | pick(.[2], .[0])",
+    json!([1, null, 3])
+);
+yields!(
+    pick_nested,
+    "{a: [{b: 1, c: 0}, {b: 2, c: 3}]} | pick(.a[1].b)",
+    json!({"a": [null, {"b": 2}]})
+);
+yields!(
+    pick_neg,
+    "[1, 2, 3] | try pick(.[-1]) catch .",
+    "cannot create negative array index -1"
+);
+yields!(
+    pick_mixed,
+    "{a: 1} | try pick(.a, .[0]) catch .",
+    "cannot create index 0 in {\"a\":1}"
+);
+
+yields!(
+    tostream_nested,
+    "{a: [1, {b: 2}], c: {}} | [tostream]",
+    json!([
+        [["a", 0], 1],
+        [["a", 1, "b"], 2],
+        [["a", 1, "b"]],
+        [["a", 1]],
+        [["c"], {}],
+        [["c"]]
+    ])
+);
+yields!(tostream_scalar, "[1 | tostream]", json!([[[], 1]]));
+yields!(
+    tostream_last,
+    "{a: [1, {b: 2}]} | [tostream]",
+    json!([
+        [["a", 0], 1],
+        [["a", 1, "b"], 2],
+        [["a", 1, "b"]],
+        [["a", 1]],
+        [["a"]]
+    ])
+);
+// the helper of `pick` and `fromstream` is not listed
+yields!(
+    builtins_putpath,
+    r#"builtins | map(select(startswith("putpath")))"#,
+    json!([])
+);
+yields!(
+    fromstream_roundtrip,
+    "[{a: [1, {b: 2}], c: []}, 3, []] | . == [fromstream(.[] | tostream)]",
+    true
+);
+yields!(
+    truncate_stream_arr,
+    "[1 | truncate_stream([[0], 1], [[1, 0], 2], [[1, 0]], [[1]])]",
+    json!([[[0], 2], [[0]]])
+);
+yields!(
+    truncate_fromstream,
+    "[fromstream(1 | truncate_stream([[1, 2], [3]] | tostream))]",
+    json!([[1, 2], [3]])
+);
+
+yields!(del_idxs, "[1, 2, 3] | del(.[0], .[1])", [3]);
+yields!(del_idx_slice, "[1, 2, 3, 4] | del(.[0], .[2:])", [2]);
+yields!(del_neg_same, "[1, 2, 3] | del(.[-1], .[2])", [1, 2]);
+yields!(del_neg_first, "[1, 2, 3, 4] | del(.[-4], .[3])", [2, 3]);
+yields!(del_dup, "[1, 2, 3] | del(.[0], .[0])", [2, 3]);
+yields!(del_neg_out, "[1, 2] | try del(.[-3]) catch 0", 0);
+yields!(
+    del_neg_nested,
+    "[[1, 2], [3, 4]] | del(.[-1][-1], .[1][0])",
+    json!([[1, 2], []])
+);
+yields!(
+    del_select,
+    "[1, 2, 3, 4] | del(.[] | select(. > 2))",
+    [1, 2]
+);
+yields!(
+    del_nested,
+    r#"{"a": {"b": 1, "c": 2}} | del(.a.b, .a)"#,
+    json!({})
+);
+
 const RECURSE_PATHS: &str = "def paths:
   { x: ., p: [] } |
   recurse((.x | keys_unsorted?)[] as $k | .x |= .[$k] | .p += [$k]) |
@@ -134,3 +266,29 @@ yields!(
     r#"[0, 0 == 0, {}.a, "hello", {}, [] | @json]"#,
     ["0", "true", "null", "\"hello\"", "{}", "[]"]
 );
+// `@text` interpolates strings verbatim, whereas `@json` quotes them
+yields!(
+    format_text_json_interpolated,
+    r#""a\"b" | [@text "<\(.)>", @json "<\(.)>"]"#,
+    ["<a\"b>", r#"<"a\"b">"#]
+);
+yields!(
+    format_text_json_interpolated_values,
+    r#"[1, null, [2], {"a": "b"}] | [@text "\(.[])", @json "\(.[])"]"#,
+    [
+        "1",
+        "null",
+        "[2]",
+        r#"{"a":"b"}"#,
+        "1",
+        "null",
+        "[2]",
+        r#"{"a":"b"}"#
+    ]
+);
+// format filters apply only to interpolated values, not to the literal parts
+yields!(
+    format_text_json_literal,
+    r#""x" | [@text "\"\(.)\"", @json "\"\(.)\""]"#,
+    [r#""x""#, r#"""x"""#]
+);
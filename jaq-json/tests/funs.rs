@@ -35,6 +35,14 @@ fn has() {
     give(json!([0, null]), "has(0)", json!(true));
     give(json!([0, null]), "has(1)", json!(true));
     give(json!([0, null]), "has(2)", json!(false));
+    give(
+        json!([0, null]),
+        "[has(-1, -2, -3)]",
+        json!([true, true, false]),
+    );
+    give(json!([0]), "has(100000000000000000000)", json!(false));
+    give(json!([0, 1]), "[has([1], [2])]", json!([true, true]));
+    give(json!([0, 1]), "has({start: 1})", json!(true));
 
     give(json!({"a": 1, "b": null}), r#"has("a")"#, json!(true));
     give(json!({"a": 1, "b": null}), r#"has("b")"#, json!(true));
@@ -59,10 +67,48 @@ yields!(indices_arr_larger, "[1, 2] | indices([1, 2, 3])", json!([]));
 
 yields!(indices_arr_overlap, "[0, 0, 0] | indices([0, 0])", [0, 1]);
 yields!(indices_str_overlap, r#""aaa" | indices("aa")"#, [0, 1]);
+// text strings are indexed by characters, even after multi-byte characters
+yields!(
+    indices_str_emoji,
+    r#""a😀b,c😀,d" | [indices(","), indices("😀"), index(","), rindex("😀")]"#,
+    json!([[3, 6], [1, 5], 3, 5])
+);
+// byte strings are indexed by bytes
+yields!(
+    indices_bytes_emoji,
+    r#""a😀b,c😀,d" | tobytes | [indices("," | tobytes), indices("😀" | tobytes)]"#,
+    json!([[6, 12], [1, 8]])
+);
+// byte offsets equal the `utf8bytelength` of the text before each occurrence
+yields!(
+    indices_bytes_utf8bytelength,
+    r#""ä😀,b😀," | [indices(",")[] as $i | .[:$i] | utf8bytelength] == (tobytes | indices("," | tobytes))"#,
+    true
+);
+// indices can be used to slice the string
+yields!(
+    indices_str_emoji_slice,
+    r#""😀😀x😀x" | [indices("x")[] as $i | .[$i:]]"#,
+    ["x😀x", "x"]
+);
 yields!(indices_str_gb1, r#""🇬🇧!" | indices("!")"#, [2]);
 yields!(indices_str_gb2, r#""🇬🇧🇬🇧" | indices("🇬🇧")"#, [0, 2]);
+// byte strings are indexed by bytes
+yields!(
+    indices_bytes_gb,
+    r#""🇬🇧!" | tobytes | indices("!" | tobytes)"#,
+    [8]
+);
 
 yields!(length_str_foo, r#""ƒoo" | length"#, 3);
+yields!(
+    length_bytes,
+    r#""äb" | [length, (tobytes | length)]"#,
+    [2, 3]
+);
+yields!(index_bytes, r#""äb" | tobytes | .[0]"#, 195);
+yields!(slice_bytes, r#""äb" | tobytes | .[0:2] | tostring"#, "ä");
+yields!(tobytes_tostring, "[104, 105] | tobytes | tostring", "hi");
 yields!(length_str_namaste, r#""नमस्ते" | length"#, 6);
 yields!(length_obj, r#"{"a": 5, "b": 3} | length"#, 2);
 yields!(length_int_pos, " 2 | length", 2);
@@ -72,9 +118,38 @@ yields!(length_float_neg, "-2.5 | length", 2.5);
 
 yields!(tojson_fl0, "1.0 | tojson", "1.0");
 yields!(tojson_fl1, "1.1 | tojson", "1.1");
+// decimal literals are preserved until they are used arithmetically
+yields!(tojson_dec, "1.10 | tojson", "1.10");
+yields!(tojson_dec_calc, "1.10 + 0 | tojson", "1.1");
+yields!(
+    fromjson_dec,
+    r#""{\"x\": 1.10, \"y\": -0.0e-0}" | fromjson | tojson"#,
+    r#"{"x":1.10,"y":-0.0e-0}"#
+);
 yields!(tojson_nan, "0.0 / 0.0 | tojson", "NaN");
 yields!(tojson_inf, "1.0 / 0.0 | tojson", "Infinity");
 yields!(tojson_ninf, "-1.0 / 0.0 | tojson", "-Infinity");
+yields!(
+    tojson_indent,
+    "{b: [1, {}], a: []} | tojson(2)",
+    "{\n  \"b\": [\n    1,\n    {}\n  ],\n  \"a\": []\n}"
+);
+yields!(tojson_indent0, "{a: [1, 2]} | tojson(0)", r#"{"a":[1,2]}"#);
+yields!(
+    tojson_indent_neg,
+    "try tojson(-1) catch .",
+    "cannot indent by -1"
+);
+yields!(
+    tojson_bigint,
+    "-340282366920938463463374607431768211456 | tojson",
+    "-340282366920938463463374607431768211456"
+);
+yields!(
+    fromjson_bigint,
+    r#""18446744073709551617" | fromjson | . - 18446744073709551616"#,
+    1
+);
 
 #[test]
 fn tonumber() {
@@ -174,3 +249,60 @@ yields!(
     r#"("%FF" | @urid) == ([255] | tobytes | tostring)"#,
     true
 );
+
+// decoded bytes are preserved even if they are not valid UTF-8
+yields!(
+    format_base64d_binary,
+    r#""+/8A" | @base64d | tobytes | [.[range(length)]]"#,
+    [251, 255, 0]
+);
+yields!(
+    format_base64d_binary_roundtrip,
+    r#"[range(256)] | tobytes | tostring | @base64 | @base64d | tobytes | [.[range(length)]] == [range(256)]"#,
+    true
+);
+
+// NaN is smaller than any number when sorting, and equal to itself,
+// so the result of sorting does not depend on where NaNs occur in the input
+yields!(
+    sort_nan,
+    "[[nan, 1, nan, 0], [0, nan, 1, nan], [1, 0, nan, nan]] | map(sort | tojson) | unique",
+    ["[NaN,NaN,0,1]"]
+);
+yields!(
+    sort_nan_many,
+    "[range(100) | if . % 3 == 0 then nan end] | sort | .[34:] == [range(100) | select(. % 3 != 0)]",
+    true
+);
+yields!(
+    min_max_nan,
+    "[1, nan, 0] | [min, max, min_by(.), max_by(.)] | tojson",
+    "[NaN,1,NaN,1]"
+);
+// comparison operators still consider NaN to be smaller than itself, like jq
+yields!(
+    cmp_nan,
+    "[nan < nan, nan > nan, nan == nan, [nan] < [nan]]",
+    [true, false, false, true]
+);
+yields!(
+    cmp_nan_obj,
+    "[{a: nan} == {a: nan}, {a: nan} != {a: nan}, {a: nan} < {a: nan}]",
+    [false, true, true]
+);
+// filters that compare values for equality agree with `==`
+yields!(index_nan, "[nan] | index(nan)", json!(null));
+yields!(indices_nan, "[nan] | indices(nan)", json!([]));
+yields!(indices_nan_arr, "[nan, 1] | indices([nan, 1])", json!([]));
+yields!(contains_nan, "[nan] | contains([nan])", false);
+yields!(
+    sub_nan,
+    "[nan, 1, [nan]] - [nan, 1, [nan]] | tojson",
+    "[NaN,[NaN]]"
+);
+yields!(unique_nan, "[nan, 1, nan] | unique | tojson", "[NaN,1]");
+yields!(
+    group_by_nan,
+    "[nan, nan] | group_by(.) | tojson",
+    "[[NaN,NaN]]"
+);
@@ -116,6 +116,8 @@ impl Settings {
             sep_space: !self.compact,
             styles: html_styles(),
             sort_keys: false,
+            float_prec: None,
+            ascii: false,
         }
     }
 
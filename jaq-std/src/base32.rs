@@ -0,0 +1,67 @@
+//! Base32 encoding and decoding as specified in RFC 4648.
+
+use alloc::vec::Vec;
+
+const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Encode bytes to Base32, padding the output with `=` to a multiple of 8 characters.
+pub fn encode(s: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity((s.len() + 4) / 5 * 8);
+    for chunk in s.chunks(5) {
+        let mut buf = [0; 5];
+        buf[..chunk.len()].copy_from_slice(chunk);
+        let n = buf.iter().fold(0u64, |acc, b| acc << 8 | u64::from(*b));
+        // number of output characters that carry data, the rest is padding
+        let data = (chunk.len() * 8 + 4) / 5;
+        out.extend((0..8).map(|i| {
+            if i < data {
+                ALPHABET[(n >> (35 - 5 * i)) as usize & 31]
+            } else {
+                b'='
+            }
+        }));
+    }
+    out
+}
+
+fn digit(c: u8) -> Option<u64> {
+    match c {
+        b'A'..=b'Z' => Some(u64::from(c - b'A')),
+        b'2'..=b'7' => Some(u64::from(c - b'2') + 26),
+        _ => None,
+    }
+}
+
+/// Decode padded Base32.
+///
+/// This fails if the input contains invalid characters or invalid padding.
+pub fn decode(s: &[u8]) -> Result<Vec<u8>, &'static str> {
+    if s.len() % 8 != 0 {
+        return Err("Base32 input length is not a multiple of 8");
+    }
+    let mut out = Vec::with_capacity(s.len() / 8 * 5);
+    let mut chunks = s.chunks(8).peekable();
+    while let Some(chunk) = chunks.next() {
+        let data = chunk.iter().position(|c| *c == b'=').unwrap_or(8);
+        // padding may only occur at the end of the input
+        let padding_ok = data == 8 || chunks.peek().is_none();
+        let len = match data {
+            8 => 5,
+            7 => 4,
+            5 => 3,
+            4 => 2,
+            2 => 1,
+            _ => 0,
+        };
+        if !padding_ok || len == 0 || chunk[data..].iter().any(|c| *c != b'=') {
+            return Err("invalid Base32 padding");
+        }
+        let mut n = 0;
+        for (i, c) in chunk[..data].iter().enumerate() {
+            let d = digit(*c).ok_or("invalid Base32 character")?;
+            n |= d << (35 - 5 * i);
+        }
+        out.extend((0..len).map(|i| (n >> (32 - 8 * i)) as u8));
+    }
+    Ok(out)
+}
@@ -1,6 +1,140 @@
 //! Native implementations of `inputs` and `input`.
+//!
+//! The inputs are provided by an arbitrary iterator, which
+//! `inputs` and `input` consume only on demand.
+//! Because filter outputs are computed lazily as well,
+//! this allows to feed inputs incrementally, e.g. from a channel,
+//! and to pull the outputs of a filter as soon as they are available.
+//! When a filter requests an input that is not yet available,
+//! the input iterator is responsible for waiting for it
+//! (in the example below, the channel receiver blocks).
+//! The examples omit parts of the global data setup;
+//! see [`jaq_core::native`] for a complete one.
+//!
+//! ~~~
+//! # use jaq_core::load::{Arena, File, Loader};
+//! # use jaq_core::{data, unwrap_valr, Compiler, Ctx, DataT, Lut, Vars};
+//! # use jaq_json::Val;
+//! use jaq_std::input::{self, HasInputs, Inputs, RcIter};
+//! use std::sync::mpsc::channel;
+//!
+//! # struct DataKind;
+//! #
+//! # impl DataT for DataKind {
+//! #     type V<'a> = Val;
+//! #     type Data<'a> = &'a Data<'a>;
+//! # }
+//! #
+//! struct Data<'a> {
+//!     lut: &'a Lut<DataKind>,
+//!     inputs: Inputs<'a, Val>,
+//! }
+//!
+//! # impl<'a> data::HasLut<'a, DataKind> for &'a Data<'a> {
+//! #     fn lut(&self) -> &'a Lut<DataKind> {
+//! #         self.lut
+//! #     }
+//! # }
+//! #
+//! impl<'a> HasInputs<'a, Val> for &'a Data<'a> {
+//!     fn inputs(&self) -> Inputs<'a, Val> {
+//!         self.inputs
+//!     }
+//! }
+//!
+//! # let loader = Loader::new(jaq_core::defs().chain(jaq_std::defs()));
+//! # let arena = Arena::default();
+//! let program = File { code: "inputs * 2", path: () };
+//! # let modules = loader.load(&arena, program).unwrap();
+//! let input_funs = input::funs().into_vec().into_iter();
+//! let funs = jaq_core::funs().chain(jaq_std::funs()).chain(jaq_json::funs());
+//! let filter = Compiler::default()
+//!     .with_funs(funs.chain(input_funs.map(jaq_core::native::run)))
+//!     .compile(modules)
+//!     .unwrap();
+//!
+//! // the sender may also be moved to another thread
+//! let (tx, rx) = channel::<isize>();
+//! let inputs = RcIter::new(rx.into_iter().map(|i| Ok(Val::from(i))));
+//! let data = Data { lut: &filter.lut, inputs: &inputs };
+//! let ctx = Ctx::<DataKind>::new(&data, Vars::new([]));
+//! let mut out = filter.id.run((ctx, Val::Null)).map(unwrap_valr);
+//!
+//! tx.send(1).unwrap();
+//! assert_eq!(out.next(), Some(Ok(Val::from(2isize))));
+//! tx.send(2).unwrap();
+//! assert_eq!(out.next(), Some(Ok(Val::from(4isize))));
+//! // closing the channel ends `inputs`
+//! drop(tx);
+//! assert_eq!(out.next(), None);
+//! ~~~
+//!
+//! The filter `input_line_number` yields the line of the most recently consumed input.
+//! For this, the global data has to implement [`HasInputs::input_line_number`]; e.g.
+//! when every line contains one input value, it suffices to count lines:
+//!
+//! ~~~
+//! # use jaq_core::load::{Arena, File, Loader};
+//! # use jaq_core::{data, unwrap_valr, Compiler, Ctx, DataT, Lut, Vars};
+//! # use jaq_json::Val;
+//! # use jaq_std::input::{self, HasInputs, Inputs, RcIter};
+//! use core::cell::Cell;
+//!
+//! # struct DataKind;
+//! #
+//! # impl DataT for DataKind {
+//! #     type V<'a> = Val;
+//! #     type Data<'a> = &'a Data<'a>;
+//! # }
+//! #
+//! struct Data<'a> {
+//!     lut: &'a Lut<DataKind>,
+//!     inputs: Inputs<'a, Val>,
+//!     line: &'a Cell<usize>,
+//! }
+//!
+//! # impl<'a> data::HasLut<'a, DataKind> for &'a Data<'a> {
+//! #     fn lut(&self) -> &'a Lut<DataKind> {
+//! #         self.lut
+//! #     }
+//! # }
+//! #
+//! impl<'a> HasInputs<'a, Val> for &'a Data<'a> {
+//!     fn inputs(&self) -> Inputs<'a, Val> {
+//!         self.inputs
+//!     }
+//!
+//!     fn input_line_number(&self) -> Option<usize> {
+//!         Some(self.line.get())
+//!     }
+//! }
+//!
+//! # let loader = Loader::new(jaq_core::defs().chain(jaq_std::defs()));
+//! # let arena = Arena::default();
+//! let program = File { code: "inputs | [., input_line_number]", path: () };
+//! # let modules = loader.load(&arena, program).unwrap();
+//! let input_funs = input::funs().into_vec().into_iter();
+//! let funs = jaq_core::funs().chain(jaq_std::funs()).chain(jaq_json::funs());
+//! let filter = Compiler::default()
+//!     .with_funs(funs.chain(input_funs.map(jaq_core::native::run)))
+//!     .compile(modules)
+//!     .unwrap();
+//!
+//! let line = Cell::new(0);
+//! let lines = "1\n2\n3".lines().map(|l| {
+//!     line.set(line.get() + 1);
+//!     jaq_json::read::parse_single(l.as_bytes()).map_err(|e| e.to_string())
+//! });
+//! let inputs = RcIter::new(lines);
+//! let data = Data { lut: &filter.lut, inputs: &inputs, line: &line };
+//! let ctx = Ctx::<DataKind>::new(&data, Vars::new([]));
+//! let out: Vec<_> = filter.id.run((ctx, Val::Null)).map(unwrap_valr).collect();
+//! let arr = |x: isize, l: isize| Ok(Val::from_iter([Val::from(x), Val::from(l)]));
+//! assert_eq!(out, [arr(1, 1), arr(2, 2), arr(3, 3)]);
+//! ~~~
 use crate::{v, Filter};
 use alloc::{boxed::Box, string::String};
+use jaq_core::box_iter::box_once;
 use jaq_core::{Cv, DataT, Error, Exn, RunPtr, ValX};
 
 /// Iterator over value results returned by the `inputs` filter.
@@ -27,6 +161,15 @@ impl<I> RcIter<I> {
 pub trait HasInputs<'a, V> {
     /// Obtain the inputs from global data.
     fn inputs(&self) -> Inputs<'a, V>;
+
+    /// Line number of the most recently consumed input, as yielded by `input_line_number`.
+    ///
+    /// The line number has to be tracked by whoever produces the inputs,
+    /// for example by counting the newlines consumed by the parser.
+    /// By default, this returns `None`, making `input_line_number` fail.
+    fn input_line_number(&self) -> Option<usize> {
+        None
+    }
 }
 
 impl<'a, V> HasInputs<'a, V> for Inputs<'a, V> {
@@ -35,14 +178,23 @@ impl<'a, V> HasInputs<'a, V> for Inputs<'a, V> {
     }
 }
 
-/// The `inputs` and `input` filters.
+/// The `inputs`, `input`, and `input_line_number` filters.
 pub fn funs<D: DataT>() -> Box<[Filter<RunPtr<D>>]>
 where
     for<'a> D::Data<'a>: HasInputs<'a, D::V<'a>>,
 {
     Box::new([
         ("inputs", v(0), |cv| Box::new(inputs(cv))),
-        ("input", v(0), |cv| Box::new(inputs(cv).next().into_iter())),
+        ("input", v(0), |cv| {
+            let none = || Err(Exn::from(Error::str("no more inputs")));
+            box_once(inputs(cv).next().unwrap_or_else(none))
+        }),
+        ("input_line_number", v(0), |cv| {
+            let line = cv.0.data().input_line_number();
+            let line = line.and_then(|l| isize::try_from(l).ok()).map(D::V::from);
+            let none = || Exn::from(Error::str("input line number is not available"));
+            box_once(line.ok_or_else(none))
+        }),
     ])
 }
 
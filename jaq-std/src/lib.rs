@@ -17,11 +17,16 @@ extern crate alloc;
 #[cfg(feature = "std")]
 extern crate std;
 
+#[cfg(feature = "format")]
+mod base32;
 pub mod input;
 #[cfg(feature = "math")]
 mod math;
+#[cfg(feature = "std")]
+pub mod os;
 #[cfg(feature = "regex")]
 mod regex;
+pub mod sink;
 #[cfg(feature = "time")]
 mod time;
 
@@ -230,6 +235,44 @@ trait ValTx: ValT + Sized {
         })
     }
 
+    /// Helper function to repeatedly strip away the prefix or suffix of a string.
+    ///
+    /// If `fix` is empty, return the original string.
+    fn strip_fix_all<F>(self, fix: &Self, f: F) -> Result<Self, Error<Self>>
+    where
+        F: for<'a> Fn(&'a [u8], &[u8]) -> Option<&'a [u8]>,
+    {
+        let (mut s, fix) = (self.try_as_bytes()?, fix.try_as_bytes()?);
+        if !fix.is_empty() {
+            while let Some(sub) = f(s, fix) {
+                s = sub;
+            }
+        }
+        Ok(self.as_sub_str(s))
+    }
+
+    /// Split a string by `sep` at most `n` times,
+    /// keeping the remainder of the string in the last element.
+    fn splitn_str(&self, sep: &Self, n: usize) -> ValR<Self> {
+        let (mut s, sep) = (self.try_as_bytes()?, sep.try_as_bytes()?);
+        let mut parts = Vec::new();
+        if sep.is_empty() {
+            // like `. / ""`, split into characters
+            while parts.len() < n && !s.is_empty() {
+                let (_, size) = bstr::decode_utf8(s);
+                let (c, rest) = s.split_at(size);
+                parts.push(c);
+                s = rest;
+            }
+            if !s.is_empty() {
+                parts.push(s)
+            }
+        } else if !s.is_empty() {
+            parts.extend(s.splitn_str(n.saturating_add(1), sep))
+        }
+        Ok(parts.into_iter().map(|p| self.as_sub_str(p)).collect())
+    }
+
     fn fail_str(&self) -> Error<Self> {
         Error::typ(self.clone(), "string")
     }
@@ -256,7 +299,13 @@ fn sort_by<'a, V: ValT>(xs: &mut [V], f: impl Fn(V) -> ValXs<'a, V>) -> Result<(
 }
 
 /// Group an array by the given function.
-fn group_by<'a, V: ValT>(xs: Vec<V>, f: impl Fn(V) -> ValXs<'a, V>) -> ValX<'a, V> {
+///
+/// Groups are sorted by their keys, and elements of every group
+/// keep the order in which they appear in the original array.
+fn group_by<'a, V: ValT>(
+    xs: Vec<V>,
+    f: impl Fn(V) -> ValXs<'a, V>,
+) -> Result<Vec<Vec<V>>, Exn<'a, V>> {
     let mut yx: Vec<(Vec<V>, V)> = xs
         .into_iter()
         .map(|x| Ok((f(x.clone()).collect::<Result<_, _>>()?, x)))
@@ -270,17 +319,61 @@ fn group_by<'a, V: ValT>(xs: Vec<V>, f: impl Fn(V) -> ValXs<'a, V>) -> ValX<'a,
         let mut group = Vec::from([first_x]);
         for (y, x) in yx {
             if group_y != y {
-                grouped.push(V::from_iter(core::mem::take(&mut group)));
+                grouped.push(core::mem::take(&mut group));
                 group_y = y;
             }
             group.push(x);
         }
         if !group.is_empty() {
-            grouped.push(V::from_iter(group));
+            grouped.push(group);
         }
     }
 
-    Ok(V::from_iter(grouped))
+    Ok(grouped)
+}
+
+/// Flatten an array, descending into at most `depth` levels of nested arrays.
+///
+/// If `depth` is `None`, descend into all nested arrays.
+fn flatten<V: ValT>(x: V, depth: Option<usize>, out: &mut Vec<V>) {
+    if depth == Some(0) {
+        return out.push(x);
+    }
+    match x.into_seq::<Vec<_>>() {
+        Ok(a) => a
+            .into_iter()
+            .for_each(|x| flatten(x, depth.map(|d| d - 1), out)),
+        Err(x) => out.push(x),
+    }
+}
+
+/// Remove a prefix or suffix from an array, using `f` to find it.
+///
+/// If `f` does not find it, return the original array.
+fn strip_arr<V: ValT, F>(v: V, fix: V, f: F) -> ValR<V>
+where
+    F: for<'a> FnOnce(&'a [V], &[V]) -> Option<&'a [V]>,
+{
+    let (a, fix) = (v.into_vec()?, fix.into_vec()?);
+    Ok(match f(&a, &fix) {
+        Some(sub) => sub.iter().cloned().collect(),
+        None => V::from_iter(a),
+    })
+}
+
+/// Return whether an array or string `v` has the given prefix/suffix `fix`.
+///
+/// If `v` is an array, `fix` must be an array, else both must be strings.
+fn has_fix<V: ValT>(
+    v: V,
+    fix: V,
+    arr: fn(&[V], &[V]) -> bool,
+    str: fn(&[u8], &[u8]) -> bool,
+) -> ValR<V> {
+    match v.into_seq::<Vec<_>>() {
+        Ok(a) => Ok(arr(&a, &fix.into_vec()?).into()),
+        Err(v) => Ok(str(v.try_as_bytes()?, fix.try_as_bytes()?).into()),
+    }
 }
 
 /// Get the minimum or maximum element from an array according to the given function.
@@ -342,23 +435,112 @@ impl Iterator for Explode<'_> {
 }
 
 /// Convert an array of Unicode codepoints (with negative integers representing UTF-8 errors) into a string.
+///
+/// On failure, the error contains the offending value and its index.
 fn implode<V: ValT>(xs: &[V]) -> Result<Vec<u8>, Error<V>> {
     let mut v = Vec::with_capacity(xs.len());
-    for x in xs {
-        // on 32-bit systems, some high u32 values cannot be represented as isize
-        let i = x.try_as_isize()?;
-        if let Ok(b) = u8::try_from(-i) {
-            v.push(b)
-        } else {
-            // may fail e.g. on `[1114112] | implode`
-            let c = u32::try_from(i).ok().and_then(char::from_u32);
-            let c = c.ok_or_else(|| Error::str(format_args!("cannot use {i} as character")))?;
-            v.extend(c.encode_utf8(&mut [0; 4]).as_bytes())
-        }
+    for (idx, x) in xs.iter().enumerate() {
+        // may fail e.g. on `[1114112] | implode`
+        push_codepoint(&mut v, x).ok_or_else(|| {
+            let post = alloc::format!(" at index {idx} as character");
+            Error::with_val("cannot use ", x.clone(), post)
+        })?
     }
     Ok(v)
 }
 
+/// Append a Unicode codepoint (or a negative integer representing a UTF-8 error) to a string.
+///
+/// Return `None` if the value does not represent a codepoint.
+fn push_codepoint<V: ValT>(v: &mut Vec<u8>, x: &V) -> Option<()> {
+    // on 32-bit systems, some high u32 values cannot be represented as isize
+    let i = x.as_isize()?;
+    if let Ok(b) = u8::try_from(-i) {
+        v.push(b)
+    } else {
+        let c = u32::try_from(i).ok().and_then(char::from_u32)?;
+        v.extend(c.encode_utf8(&mut [0; 4]).as_bytes())
+    }
+    Some(())
+}
+
+/// Map ASCII letters in a string or in an array of code points.
+///
+/// Array elements that are not ASCII code points are left unchanged.
+fn ascii_case<V: ValT>(v: V, f: fn(&u8) -> u8) -> ValR<V> {
+    let ascii = |c: &V| c.as_isize().and_then(|i| u8::try_from(i).ok());
+    match v.into_seq::<Vec<_>>() {
+        Ok(a) => Ok(a
+            .into_iter()
+            .map(|c| match ascii(&c) {
+                Some(b) if b.is_ascii() => (f(&b) as isize).into(),
+                _ => c,
+            })
+            .collect()),
+        Err(v) => v.map_utf8_str(|s| s.iter().map(f).collect::<Vec<_>>()),
+    }
+}
+
+/// Map ASCII letters in a string to lowercase, leaving other values unchanged.
+fn ascii_fold<V: ValT>(v: V) -> V {
+    match v.as_utf8_bytes() {
+        Some(s) => V::from_utf8_bytes(s.to_ascii_lowercase()),
+        None => v,
+    }
+}
+
+/// Return true if the string is a number literal as permitted by JSON.
+fn is_num(s: &[u8]) -> bool {
+    let digits = |s: &[u8]| s.iter().take_while(|c| c.is_ascii_digit()).count();
+    let s = s.strip_prefix(b"-").unwrap_or(s);
+    let int = digits(s);
+    if int == 0 || (int > 1 && s[0] == b'0') {
+        return false;
+    }
+    let mut s = &s[int..];
+    if let Some(frac) = s.strip_prefix(b".") {
+        let n = digits(frac);
+        if n == 0 {
+            return false;
+        }
+        s = &frac[n..];
+    }
+    if let Some(exp) = s.strip_prefix(b"e").or_else(|| s.strip_prefix(b"E")) {
+        let exp = exp
+            .strip_prefix(b"+")
+            .or_else(|| exp.strip_prefix(b"-"))
+            .unwrap_or(exp);
+        let n = digits(exp);
+        if n == 0 {
+            return false;
+        }
+        s = &exp[n..];
+    }
+    s.is_empty()
+}
+
+/// Yield -1, 0, or 1 depending on the sign of a number, and NaN for NaN.
+fn signum<V: ValT>(v: V) -> ValR<V> {
+    let f = v.try_as_f64()?;
+    let sign = isize::from(f > 0.0) - isize::from(f < 0.0);
+    Ok(if f.is_nan() { v } else { sign.into() })
+}
+
+/// Convert a number or a string containing a number to a number.
+fn tonumber<V: ValT>(v: V) -> ValR<V> {
+    let fail = |v| Error::with_val("cannot parse ", v, " as number");
+    if v.as_f64().is_some() {
+        return Ok(v);
+    }
+    match v.as_utf8_bytes() {
+        Some(b"NaN") => Ok(f64::NAN.into()),
+        Some(b"Infinity") => Ok(f64::INFINITY.into()),
+        Some(b"-Infinity") => Ok(f64::NEG_INFINITY.into()),
+        Some(s) if is_num(s) => V::from_num(core::str::from_utf8(s).unwrap()),
+        _ => Err(fail(v)),
+    }
+}
+
 fn once_or_empty<'a, T: 'a, E: 'a>(r: Result<Option<T>, E>) -> BoxIter<'a, Result<T, E>> {
     Box::new(r.transpose().into_iter())
 }
@@ -373,6 +555,13 @@ where
         ("floor", v(0), |cv| bome(cv.1.round(f64::floor))),
         ("round", v(0), |cv| bome(cv.1.round(f64::round))),
         ("ceil", v(0), |cv| bome(cv.1.round(f64::ceil))),
+        ("abs", v(0), |cv| {
+            // negation preserves integers, including those exceeding `isize`
+            let zero = D::V::from(0isize);
+            bome(if cv.1 < zero { -cv.1 } else { Ok(cv.1) })
+        }),
+        ("signum", v(0), |cv| bome(signum(cv.1))),
+        ("tonumber", v(0), |cv| bome(tonumber(cv.1))),
         ("utf8bytelength", v(0), |cv| {
             bome(cv.1.try_as_utf8_bytes().map(|s| (s.len() as isize).into()))
         }),
@@ -383,11 +572,27 @@ where
             let implode = |s: Vec<_>| implode(&s);
             bome(cv.1.into_vec().and_then(implode).map(D::V::from_utf8_bytes))
         }),
+        ("ascii", v(0), |cv| {
+            let mut c = Vec::new();
+            let err = || Error::with_val("cannot use ", cv.1.clone(), " as character");
+            let c = push_codepoint(&mut c, &cv.1).map(|()| c).ok_or_else(err);
+            bome(c.map(D::V::from_utf8_bytes))
+        }),
         ("ascii_downcase", v(0), |cv| {
-            bome(cv.1.map_utf8_str(ByteSlice::to_ascii_lowercase))
+            bome(ascii_case(cv.1, u8::to_ascii_lowercase))
         }),
         ("ascii_upcase", v(0), |cv| {
-            bome(cv.1.map_utf8_str(ByteSlice::to_ascii_uppercase))
+            bome(ascii_case(cv.1, u8::to_ascii_uppercase))
+        }),
+        ("downcase", v(0), |cv| {
+            bome(cv.1.map_utf8_str(ByteSlice::to_lowercase))
+        }),
+        ("upcase", v(0), |cv| {
+            bome(cv.1.map_utf8_str(ByteSlice::to_uppercase))
+        }),
+        ("toarray", v(0), |cv| {
+            let a: Result<Vec<_>, _> = cv.1.into_seq();
+            bome(Ok(a.map_or_else(|v| D::V::from_iter([v]), D::V::from_iter)))
         }),
         ("reverse", v(0), |cv| bome(cv.1.mutate_arr(|a| a.reverse()))),
         ("sort", v(0), |cv| bome(cv.1.mutate_arr(|a| a.sort()))),
@@ -396,29 +601,103 @@ where
             let f = move |v| f.run((fc.clone(), v));
             box_once(cv.1.try_mutate_arr(|a| sort_by(a, f)))
         }),
+        ("sort_by_ci", f(), |mut cv| {
+            let (f, fc) = cv.0.pop_fun();
+            let f = move |v| {
+                let ys = f.run((fc.clone(), v)).map(|y| y.map(ascii_fold));
+                Box::new(ys) as ValXs<_>
+            };
+            box_once(cv.1.try_mutate_arr(|a| sort_by(a, f)))
+        }),
         ("group_by", f(), |mut cv| {
             let (f, fc) = cv.0.pop_fun();
             let f = move |v| f.run((fc.clone(), v));
-            box_once((|| group_by(cv.1.into_vec()?, f))())
+            let groups = |xs| group_by(xs, f).map(|g| g.into_iter().map(D::V::from_iter));
+            box_once((|| groups(cv.1.into_vec()?).map(D::V::from_iter))())
+        }),
+        ("unique_by", f(), |mut cv| {
+            let (f, fc) = cv.0.pop_fun();
+            let f = move |v| f.run((fc.clone(), v));
+            let first = |g: Vec<_>| g.into_iter().next();
+            let firsts = |xs| group_by(xs, f).map(|gs| gs.into_iter().filter_map(first));
+            box_once((|| firsts(cv.1.into_vec()?).map(D::V::from_iter))())
+        }),
+        ("unique", v(0), |cv| {
+            bome(cv.1.mutate_arr(|a| {
+                a.sort();
+                a.dedup();
+            }))
+        }),
+        ("ltrimarr", v(1), |cv| {
+            unary(cv, |v, pre| strip_arr(v, pre, |a, pre| a.strip_prefix(pre)))
+        }),
+        ("rtrimarr", v(1), |cv| {
+            unary(cv, |v, suf| strip_arr(v, suf, |a, suf| a.strip_suffix(suf)))
+        }),
+        ("flatten", v(0), |cv| {
+            let mut out = Vec::new();
+            flatten(cv.1, None, &mut out);
+            bome(Ok(D::V::from_iter(out)))
+        }),
+        ("flatten", v(1), |cv| {
+            unary(cv, |v, d| {
+                let d = d.as_f64().ok_or_else(|| Error::typ(d, "number"))?;
+                if d < 0.0 {
+                    return Err(Error::str("flatten depth must not be negative"));
+                }
+                // non-integer depths are rounded up, like `flatten($d - 1)` in jq
+                let d = d.ceil() as usize;
+                let mut out = Vec::new();
+                flatten(v, Some(d.saturating_add(1)), &mut out);
+                Ok(D::V::from_iter(out))
+            })
+        }),
+        // `Iterator::min` yields the first and `Iterator::max` the last of equal elements,
+        // which corresponds to `min_by_or_empty(.)` and `max_by_or_empty(.)`
+        ("min_or_empty", v(0), |cv| {
+            once_or_empty(
+                cv.1.into_vec()
+                    .map(|a| a.into_iter().min())
+                    .map_err(Exn::from),
+            )
+        }),
+        ("max_or_empty", v(0), |cv| {
+            once_or_empty(
+                cv.1.into_vec()
+                    .map(|a| a.into_iter().max())
+                    .map_err(Exn::from),
+            )
         }),
         ("min_by_or_empty", f(), |mut cv| {
             let (f, fc) = cv.0.pop_fun();
-            let f = move |a| cmp_by(a, |v| f.run((fc.clone(), v)), |my, y| y < my);
+            let f = move |a| cmp_by(a, |v| f.run((fc.clone(), v)), |my, y| y.cmp(my).is_lt());
             once_or_empty(cv.1.into_vec().map_err(Exn::from).and_then(f))
         }),
         ("max_by_or_empty", f(), |mut cv| {
             let (f, fc) = cv.0.pop_fun();
-            let f = move |a| cmp_by(a, |v| f.run((fc.clone(), v)), |my, y| y >= my);
+            let f = move |a| cmp_by(a, |v| f.run((fc.clone(), v)), |my, y| y.cmp(my).is_ge());
             once_or_empty(cv.1.into_vec().map_err(Exn::from).and_then(f))
         }),
         ("startswith", v(1), |cv| {
             unary(cv, |v, s| {
-                Ok(v.try_as_bytes()?.starts_with(s.try_as_bytes()?).into())
+                has_fix(v, s, <[_]>::starts_with, <[_]>::starts_with)
             })
         }),
         ("endswith", v(1), |cv| {
+            unary(cv, |v, s| has_fix(v, s, <[_]>::ends_with, <[_]>::ends_with))
+        }),
+        ("startswith_ci", v(1), |cv| {
             unary(cv, |v, s| {
-                Ok(v.try_as_bytes()?.ends_with(s.try_as_bytes()?).into())
+                let (v, s) = (v.try_as_bytes()?, s.try_as_bytes()?);
+                let pre = v.get(..s.len());
+                Ok(pre.is_some_and(|pre| pre.eq_ignore_ascii_case(s)).into())
+            })
+        }),
+        ("endswith_ci", v(1), |cv| {
+            unary(cv, |v, s| {
+                let (v, s) = (v.try_as_bytes()?, s.try_as_bytes()?);
+                let suf = v.len().checked_sub(s.len()).map(|i| &v[i..]);
+                Ok(suf.is_some_and(|suf| suf.eq_ignore_ascii_case(s)).into())
             })
         }),
         ("ltrimstr", v(1), |cv| {
@@ -427,6 +706,20 @@ where
         ("rtrimstr", v(1), |cv| {
             unary(cv, |v, suf| v.strip_fix(&suf, <[u8]>::strip_suffix))
         }),
+        ("ltrimstr_all", v(1), |cv| {
+            unary(cv, |v, pre| v.strip_fix_all(&pre, <[u8]>::strip_prefix))
+        }),
+        ("rtrimstr_all", v(1), |cv| {
+            unary(cv, |v, suf| v.strip_fix_all(&suf, <[u8]>::strip_suffix))
+        }),
+        ("splitn", v(2), |mut cv| {
+            let n = cv.0.pop_var();
+            let sep = cv.0.pop_var();
+            let n = n.try_as_isize().and_then(|n| {
+                usize::try_from(n).map_err(|_| Error::str("splitn count must not be negative"))
+            });
+            bome(n.and_then(|n| cv.1.splitn_str(&sep, n)))
+        }),
         ("trim", v(0), |cv| {
             bome(cv.1.trim_utf8_with(ByteSlice::trim))
         }),
@@ -466,6 +759,11 @@ where
                 vars().map(|(k, v)| (D::V::from(k), D::V::from(v))),
             ))
         }),
+        ("env_or_empty", v(1), |mut cv| {
+            let name = cv.0.pop_var();
+            let var = name.try_as_str().map(|name| std::env::var(name).ok());
+            once_or_empty(var.map(|v| v.map(D::V::from)).map_err(Exn::from))
+        }),
         ("now", v(0), |_| bome(now().map(D::V::from))),
         ("halt", v(1), |mut cv| {
             let exit_code = cv.0.pop_var().try_as_isize();
@@ -480,6 +778,23 @@ fn replace(s: &[u8], patterns: &[&str], replacements: &[&str]) -> Vec<u8> {
     ac.replace_all_bytes(s, replacements)
 }
 
+/// Percent-encode all bytes except for those that may occur unescaped in a URI.
+///
+/// These are the unreserved and reserved characters of RFC 3986.
+#[cfg(feature = "format")]
+fn encode_uri_full(s: &[u8]) -> String {
+    const KEEP: &[u8] = b"-._~:/?#[]@!$&'()*+,;=";
+    let mut out = String::with_capacity(s.len());
+    for b in s {
+        if b.is_ascii_alphanumeric() || KEEP.contains(b) {
+            out.push(char::from(*b))
+        } else {
+            out.push_str(&alloc::format!("%{b:02X}"))
+        }
+    }
+    out
+}
+
 #[cfg(feature = "format")]
 fn format<D: DataT>() -> Box<[Filter<RunPtr<D>>]>
 where
@@ -497,6 +812,9 @@ where
         ("encode_uri", v(0), |cv| {
             bome(cv.1.map_utf8_str(|s| urlencoding::encode_binary(s).to_string()))
         }),
+        ("encode_uri_full", v(0), |cv| {
+            bome(cv.1.map_utf8_str(encode_uri_full))
+        }),
         ("decode_uri", v(0), |cv| {
             bome(cv.1.map_utf8_str(|s| urlencoding::decode_binary(s).to_vec()))
         }),
@@ -513,6 +831,35 @@ where
                     .map(ValT::from_utf8_bytes)
             }))
         }),
+        ("decode_base64_lenient", v(0), |cv| {
+            use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+            use base64::{alphabet::STANDARD, Engine};
+            let padding = DecodePaddingMode::Indifferent;
+            let config = GeneralPurposeConfig::new().with_decode_padding_mode(padding);
+            let lenient = GeneralPurpose::new(&STANDARD, config);
+            // map the URL-safe alphabet to the standard one
+            let std = |c: &u8| match c {
+                b'-' => b'+',
+                b'_' => b'/',
+                c => *c,
+            };
+            bome(cv.1.try_as_utf8_bytes().and_then(|s| {
+                lenient
+                    .decode(s.iter().map(std).collect::<Vec<_>>())
+                    .map_err(Error::str)
+                    .map(ValT::from_utf8_bytes)
+            }))
+        }),
+        ("encode_base32", v(0), |cv| {
+            bome(cv.1.map_utf8_str(base32::encode))
+        }),
+        ("decode_base32", v(0), |cv| {
+            bome(cv.1.try_as_utf8_bytes().and_then(|s| {
+                base32::decode(s)
+                    .map_err(Error::str)
+                    .map(ValT::from_utf8_bytes)
+            }))
+        }),
     ])
 }
 
@@ -581,9 +928,37 @@ where
         rename("scalbln", math::fi_f!(scalbn)),
         math::if_f!(yn),
         math::fff_f!(fma),
+        ("nan", v(0), |_| bome(Ok(f64::NAN.into()))),
+        ("infinite", v(0), |_| bome(Ok(f64::INFINITY.into()))),
+        ("isnan", v(0), |cv| {
+            bome(Ok(cv.1.as_f64().is_some_and(f64::is_nan).into()))
+        }),
+        // integers that are too large for `f64` are still not infinite
+        ("isinfinite", v(0), |cv| {
+            let inf = !cv.1.is_int() && cv.1.as_f64().is_some_and(f64::is_infinite);
+            bome(Ok(inf.into()))
+        }),
+        ("isnormal", v(0), |cv| {
+            let int = cv.1.is_int();
+            let normal = |f: f64| f.is_normal() || (int && f != 0.0);
+            bome(Ok(cv.1.as_f64().is_some_and(normal).into()))
+        }),
     ])
 }
 
+#[cfg(feature = "regex")]
+fn compile_re<V: ValT>(
+    re: V,
+    flags: V,
+) -> Result<(regex_bites::bytes::Regex, regex::Flags), Error<V>> {
+    let fail_flag = |e| Error::str(format_args!("invalid regex flag: {e}"));
+    let fail_re = |e| Error::str(format_args!("invalid regex: {e}"));
+
+    let flags = regex::Flags::new(flags.try_as_str()?).map_err(fail_flag)?;
+    let re = flags.regex(re.try_as_str()?).map_err(fail_re)?;
+    Ok((re, flags))
+}
+
 #[cfg(feature = "regex")]
 fn re<'a, D: DataT>(s: bool, m: bool, mut cv: Cv<'a, D>) -> ValR<D::V<'a>>
 where
@@ -593,11 +968,7 @@ where
     let re = cv.0.pop_var();
 
     use crate::regex::Part::{Matches, Mismatch};
-    let fail_flag = |e| Error::str(format_args!("invalid regex flag: {e}"));
-    let fail_re = |e| Error::str(format_args!("invalid regex: {e}"));
-
-    let flags = regex::Flags::new(flags.try_as_str()?).map_err(fail_flag)?;
-    let re = flags.regex(re.try_as_str()?).map_err(fail_re)?;
+    let (re, flags) = compile_re(re, flags)?;
     let out = regex::regex(cv.1.try_as_utf8_bytes()?, &re, flags, (s, m));
     let sub = |s| cv.1.as_sub_str(s);
     let out = out.into_iter().map(|out| match out {
@@ -610,6 +981,28 @@ where
     out.collect()
 }
 
+/// Split a string lazily at the matches of a regex.
+#[cfg(feature = "regex")]
+fn splits<'a, D: DataT>(mut cv: Cv<'a, D>) -> ValXs<'a, D::V<'a>>
+where
+    D::V<'a>: ValT,
+{
+    let flags = cv.0.pop_var();
+    let re = cv.0.pop_var();
+    let (re, flags) = match compile_re(re, flags) {
+        Ok(ok) => ok,
+        Err(e) => return box_once(Err(e.into())),
+    };
+    if let Err(e) = cv.1.try_as_utf8_bytes() {
+        return box_once(Err(e.into()));
+    }
+    let mut splits = regex::Splits::new(re, flags);
+    Box::new(core::iter::from_fn(move || {
+        let s = cv.1.try_as_utf8_bytes().ok()?;
+        splits.next(s).map(|s| Ok(cv.1.as_sub_str(s)))
+    }))
+}
+
 #[cfg(feature = "regex")]
 fn regex<D: DataT>() -> Box<[Filter<RunPtr<D>>]>
 where
@@ -620,6 +1013,7 @@ where
         ("matches", vv(), |cv| bome(re(false, true, cv))),
         ("split_matches", vv(), |cv| bome(re(true, true, cv))),
         ("split_", vv(), |cv| bome(re(true, false, cv))),
+        ("splits_", vv(), |cv| splits(cv)),
     ])
 }
 
@@ -658,6 +1052,11 @@ where
             })
         }),
         ("mktime", v(0), |cv| bome(time::mktime(&cv.1))),
+        ("dateadd", v(2), |mut cv| {
+            let n = cv.0.pop_var();
+            let unit = cv.0.pop_var();
+            bome(unit.try_as_str().and_then(|u| time::dateadd(&cv.1, u, &n)))
+        }),
     ])
 }
 
@@ -0,0 +1,185 @@
+//! Native implementations of filters that interact with the operating system.
+//!
+//! By default, `env` and `env($name)` read the environment variables of the current process,
+//! `now` reads the system clock, and `halt` terminates the current process.
+//! This is appropriate for the jaq CLI, but not when
+//! jaq is embedded in a long-running process, such as a service, or
+//! when running untrusted filters, which should not access the system.
+//! To change this behaviour, implement [`HasOs`] for your global data and
+//! use the filters returned by [`funs`].
+//! The compiler uses the first native filter that matches a name, so
+//! chain these filters *before* [`crate::funs`] to replace the default ones.
+//! Note that the jaq CLI also provides environment variables via `$ENV`;
+//! when embedding jaq, such global variables are only available if
+//! you pass them via [`jaq_core::Ctx::new`].
+//!
+//! In the following example, `env` and `env($name)` see only a fixed set of variables,
+//! `now` and `localtime` use a fixed clock and time zone, and
+//! `halt` yields an error instead of exiting.
+//! This makes the outputs of filters reproducible, e.g. for snapshot tests.
+//! Like any other error, this error can be caught with `try`, and
+//! it is up to the caller to stop processing further outputs.
+//! The filter `halt_error` first prints its input via `stderr`,
+//! which can be captured with [`crate::sink`].
+//! As in [`crate::sink`], the setup of the global data is abbreviated.
+//!
+//! ~~~
+//! # use jaq_core::load::{Arena, File, Loader};
+//! # use jaq_core::{data, unwrap_valr, Compiler, Ctx, DataT, Error, Lut, Vars};
+//! # use jaq_json::Val;
+//! use jaq_std::os::{self, HasOs};
+//!
+//! # struct DataKind;
+//! #
+//! # impl DataT for DataKind {
+//! #     type V<'a> = Val;
+//! #     type Data<'a> = &'a Data<'a>;
+//! # }
+//! #
+//! struct Data<'a> {
+//!     lut: &'a Lut<DataKind>,
+//! }
+//!
+//! # impl<'a> data::HasLut<'a, DataKind> for &'a Data<'a> {
+//! #     fn lut(&self) -> &'a Lut<DataKind> {
+//! #         self.lut
+//! #     }
+//! # }
+//! #
+//! impl HasOs<Val> for &Data<'_> {
+//!     fn env(&self) -> Vec<(String, String)> {
+//!         Vec::from([("USER".to_string(), "jaq".to_string())])
+//!     }
+//!
+//!     fn now(&self) -> Result<f64, Error<Val>> {
+//!         Ok(86400.0)
+//!     }
+//!
+//!     fn time_zone(&self) -> os::TimeZone {
+//!         // two hours ahead of UTC (POSIX inverts the sign)
+//!         os::TimeZone::posix("XYZ-2").unwrap()
+//!     }
+//!
+//!     fn halt(&self, exit_code: isize) -> Error<Val> {
+//!         Error::str(format!("halted with exit code {exit_code}"))
+//!     }
+//! }
+//!
+//! # let loader = Loader::new(jaq_core::defs().chain(jaq_std::defs()));
+//! # let arena = Arena::default();
+//! let program = File { code: r#"env.USER, env("HOME"), (now | strflocaltime("%F %R %z")), halt(3), 2"#, path: () };
+//! # let modules = loader.load(&arena, program).unwrap();
+//! let os_funs = os::funs().into_vec().into_iter().map(jaq_core::native::run);
+//! let funs = jaq_core::funs().chain(jaq_std::funs()).chain(jaq_json::funs());
+//! let filter = Compiler::default()
+//!     .with_funs(os_funs.chain(funs))
+//!     .compile(modules)
+//!     .unwrap();
+//!
+//! let data = Data { lut: &filter.lut };
+//! let ctx = Ctx::<DataKind>::new(&data, Vars::new([]));
+//! let out: Vec<_> = filter.id.run((ctx, Val::Null)).map(unwrap_valr).collect();
+//! let err = Error::str("halted with exit code 3");
+//! let user = Val::from("jaq".to_string());
+//! let time = Val::from("1970-01-02 02:00 +0200".to_string());
+//! assert_eq!(out, [Ok(user), Ok(Val::Null), Ok(time), Err(err), Ok(Val::from(2isize))]);
+//! ~~~
+use crate::{v, Filter, ValT, ValTx};
+use alloc::{boxed::Box, string::String, vec::Vec};
+use jaq_core::native::bome;
+use jaq_core::{DataT, Error, Exn, RunPtr, ValT as _};
+#[cfg(feature = "time")]
+pub use jiff::tz::TimeZone;
+
+/// Global data that determines how filters interact with the operating system.
+///
+/// Every method has a default implementation that
+/// corresponds to the behaviour of the filters in [`crate::funs`].
+pub trait HasOs<V> {
+    /// Environment variables, as yielded by `env`.
+    fn env(&self) -> Vec<(String, String)> {
+        std::env::vars().collect()
+    }
+
+    /// Value of the environment variable `name`, as yielded by `env($name)`.
+    ///
+    /// By default, this looks up `name` in [`Self::env`], so
+    /// overriding [`Self::env`] suffices to hide variables from filters.
+    /// Override this method only to make the lookup faster;
+    /// it should then hide the same variables as [`Self::env`].
+    fn env_var(&self, name: &str) -> Option<String> {
+        let mut vars = self.env().into_iter();
+        vars.find_map(|(k, v)| (k == name).then_some(v))
+    }
+
+    /// Current time in seconds since the Unix epoch, as yielded by `now`.
+    fn now(&self) -> Result<f64, Error<V>>
+    where
+        V: From<String>,
+    {
+        crate::now()
+    }
+
+    /// Time zone used by `localtime` and `strflocaltime`.
+    ///
+    /// By default, this is the time zone of the system.
+    #[cfg(feature = "time")]
+    fn time_zone(&self) -> TimeZone {
+        TimeZone::system()
+    }
+
+    /// Handle `halt($exit_code)`.
+    ///
+    /// The returned error is yielded by `halt`.
+    /// By default, this terminates the process with the given exit code.
+    fn halt(&self, exit_code: isize) -> Error<V> {
+        std::process::exit(exit_code as i32)
+    }
+}
+
+/// The `env`, `env($name)`, `now`, `localtime`, `strflocaltime`, and `halt` filters, using [`HasOs`].
+///
+/// The filters `localtime` and `strflocaltime` are only included
+/// if the `time` feature is enabled.
+pub fn funs<D: DataT>() -> Box<[Filter<RunPtr<D>>]>
+where
+    for<'a> D::Data<'a>: HasOs<D::V<'a>>,
+    for<'a> D::V<'a>: ValT,
+{
+    let funs: Box<[Filter<RunPtr<D>>]> = Box::new([
+        ("env", v(0), |cv| {
+            let vars = cv.0.data().env().into_iter();
+            bome(D::V::from_map(
+                vars.map(|(k, v)| (D::V::from(k), D::V::from(v))),
+            ))
+        }),
+        ("env_or_empty", v(1), |mut cv| {
+            let name = cv.0.pop_var();
+            let var = name.try_as_str().map(|name| cv.0.data().env_var(name));
+            crate::once_or_empty(var.map(|v| v.map(D::V::from)).map_err(Exn::from))
+        }),
+        ("now", v(0), |cv| bome(cv.0.data().now().map(D::V::from))),
+        ("halt", v(1), |mut cv| {
+            let exit_code = cv.0.pop_var().try_as_isize();
+            bome(exit_code.and_then(|code| Err(cv.0.data().halt(code))))
+        }),
+    ]);
+    #[cfg(feature = "time")]
+    let time: Box<[Filter<RunPtr<D>>]> = Box::new([
+        ("localtime", v(0), |cv| {
+            let tz = cv.0.data().time_zone();
+            bome(crate::time::gmtime(&cv.1, tz))
+        }),
+        ("strflocaltime", v(1), |mut cv| {
+            let fmt = cv.0.pop_var();
+            let tz = cv.0.data().time_zone();
+            bome(
+                fmt.try_as_str()
+                    .and_then(|fmt| crate::time::strftime(&cv.1, fmt, tz)),
+            )
+        }),
+    ]);
+    #[cfg(feature = "time")]
+    let funs = [funs, time].concat().into_boxed_slice();
+    funs
+}
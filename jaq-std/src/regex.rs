@@ -3,7 +3,7 @@
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 use bstr::ByteSlice;
-use regex_bites::bytes::{self as regex, Error, Regex, RegexBuilder};
+use regex_bites::bytes::{self as regex, Captures, Error, Regex, RegexBuilder};
 
 #[derive(Copy, Clone, Default)]
 pub struct Flags {
@@ -17,13 +17,16 @@ pub struct Flags {
     m: bool,
     // single-line mode: allow . to match \n
     s: bool,
-    // greedy
+    // swap greedy and lazy quantifiers
     l: bool,
     // extended mode: ignore whitespace and allow line comments (starting with `#`)
     x: bool,
 }
 
 impl Flags {
+    /// Parse flags as given to `test`, `match` etc.
+    ///
+    /// Every character is a flag; an unknown flag is returned as error.
     pub fn new(flags: &str) -> Result<Self, char> {
         let mut out = Self::default();
         for flag in flags.chars() {
@@ -62,6 +65,12 @@ impl Flags {
             .ignore_whitespace(self.x)
     }
 
+    /// Compile a regex with the given flags.
+    ///
+    /// This keeps the default limits of the regex backend:
+    /// the compiled regex may take roughly 10 MiB, and
+    /// the regex may be nested at most 50 levels deep.
+    /// Regexes that exceed these limits yield an error.
     pub fn regex(self, re: &str) -> Result<Regex, Error> {
         let mut builder = RegexBuilder::new(re);
         self.impact(&mut builder).build()
@@ -127,6 +136,36 @@ impl<'a> Match<&'a [u8], &'a str> {
     }
 }
 
+/// Search state for successive non-overlapping matches of a regex.
+///
+/// This behaves like [`Regex::captures_iter`],
+/// but it does not hold a reference to the haystack,
+/// so that the search can be resumed on a value that is owned elsewhere.
+#[derive(Default)]
+struct Search {
+    at: usize,
+    last_end: Option<usize>,
+}
+
+impl Search {
+    fn next<'h>(&mut self, re: &Regex, s: &'h [u8]) -> Option<Captures<'h>> {
+        // `captures_at` may panic if `at > s.len()`, which happens
+        // after an empty match at the end of `s` or in `Splits::next`
+        let captures_at = |at| (at <= s.len()).then(|| re.captures_at(s, at)).flatten();
+        let mut c = captures_at(self.at)?;
+        let mut m = c.get(0).unwrap();
+        // prevent an empty match directly after the previous match
+        if m.as_bytes().is_empty() && Some(m.end()) == self.last_end {
+            self.at += core::cmp::max(1, bstr::decode_utf8(&s[self.at..]).1);
+            c = captures_at(self.at)?;
+            m = c.get(0).unwrap();
+        }
+        self.at = m.end();
+        self.last_end = Some(m.end());
+        Some(c)
+    }
+}
+
 pub enum Part<B, S> {
     Matches(Vec<Match<B, S>>),
     Mismatch(B),
@@ -150,7 +189,8 @@ pub fn regex<'a>(
     let mut bc = ByteChar::new(s);
     let mut out = Vec::new();
 
-    for c in re.captures_iter(s) {
+    let mut search = Search::default();
+    while let Some(c) = search.next(re, s) {
         let whole = c.get(0).unwrap();
         if flags.ignore_empty() && whole.as_bytes().is_empty() {
             continue;
@@ -173,3 +213,47 @@ pub fn regex<'a>(
     }
     out
 }
+
+/// Lazy variant of [`regex`] that only yields the strings that do *not* match.
+///
+/// Unlike [`regex`], this owns the regex, and
+/// takes the input string anew on every call to [`Splits::next`],
+/// which must always be the same string.
+pub struct Splits {
+    re: Regex,
+    flags: Flags,
+    search: Search,
+    // `None` once the last part has been yielded
+    last_byte: Option<usize>,
+}
+
+impl Splits {
+    pub fn new(re: Regex, flags: Flags) -> Self {
+        let search = Search::default();
+        let last_byte = Some(0);
+        Self {
+            re,
+            flags,
+            search,
+            last_byte,
+        }
+    }
+
+    pub fn next<'a>(&mut self, s: &'a [u8]) -> Option<&'a [u8]> {
+        let last_byte = self.last_byte?;
+        while let Some(c) = self.search.next(&self.re, s) {
+            let whole = c.get(0).unwrap();
+            if self.flags.ignore_empty() && whole.as_bytes().is_empty() {
+                continue;
+            }
+            self.last_byte = Some(whole.end());
+            if !self.flags.global() {
+                // do not search further after the next call
+                self.search.at = s.len() + 1;
+            }
+            return Some(&s[last_byte..whole.start()]);
+        }
+        self.last_byte = None;
+        Some(&s[last_byte..])
+    }
+}
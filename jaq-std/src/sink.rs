@@ -0,0 +1,109 @@
+//! Native implementations of `debug` and `stderr` that report to a custom sink.
+//!
+//! By default, `debug` and `stderr` emit their messages via the `log` crate.
+//! When embedding jaq, it is often more convenient to
+//! capture these messages programmatically, e.g. to show them in a user interface.
+//! For this, implement [`HasSink`] for your global data and
+//! use the filters returned by [`funs`].
+//! The compiler uses the first native filter that matches a name, so
+//! chain these filters *before* [`crate::funs`] to replace the default ones.
+//!
+//! The following example collects all messages in a vector.
+//! For brevity, it omits parts of the global data setup,
+//! which is shown in full in [`jaq_core::native`].
+//!
+//! ~~~
+//! use core::cell::RefCell;
+//! # use jaq_core::load::{Arena, File, Loader};
+//! # use jaq_core::{data, unwrap_valr, Compiler, Ctx, DataT, Lut, Vars};
+//! # use jaq_json::Val;
+//! use jaq_std::sink::{self, Event, HasSink};
+//!
+//! # struct DataKind;
+//! #
+//! # impl DataT for DataKind {
+//! #     type V<'a> = Val;
+//! #     type Data<'a> = &'a Data<'a>;
+//! # }
+//! #
+//! struct Data<'a> {
+//!     lut: &'a Lut<DataKind>,
+//!     events: RefCell<Vec<Event<Val>>>,
+//! }
+//!
+//! # impl<'a> data::HasLut<'a, DataKind> for &'a Data<'a> {
+//! #     fn lut(&self) -> &'a Lut<DataKind> {
+//! #         self.lut
+//! #     }
+//! # }
+//! #
+//! impl HasSink<Val> for &Data<'_> {
+//!     fn emit(&self, event: Event<Val>) {
+//!         self.events.borrow_mut().push(event)
+//!     }
+//! }
+//!
+//! # let loader = Loader::new(jaq_core::defs().chain(jaq_std::defs()));
+//! # let arena = Arena::default();
+//! let program = File { code: r#"debug("got \(.)") | stderr | . + 1"#, path: () };
+//! # let modules = loader.load(&arena, program).unwrap();
+//! let sink_funs = sink::funs().into_vec().into_iter().map(jaq_core::native::run);
+//! let funs = jaq_core::funs().chain(jaq_std::funs()).chain(jaq_json::funs());
+//! let filter = Compiler::default()
+//!     .with_funs(sink_funs.chain(funs))
+//!     .compile(modules)
+//!     .unwrap();
+//!
+//! let events = RefCell::new(Vec::new());
+//! let data = Data { lut: &filter.lut, events };
+//! let ctx = Ctx::<DataKind>::new(&data, Vars::new([]));
+//! let out: Vec<_> = filter.id.run((ctx, Val::from(1isize))).map(unwrap_valr).collect();
+//! assert_eq!(out, [Ok(Val::from(2isize))]);
+//! let events = data.events.take();
+//! let got = Val::from("got 1".to_string());
+//! assert_eq!(events, [Event::Debug(got), Event::Stderr(Val::from(1isize))]);
+//! ~~~
+use crate::{v, Filter};
+use alloc::boxed::Box;
+use jaq_core::{DataT, RunPtr};
+
+/// Message emitted by a filter.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event<V> {
+    /// Message emitted by `debug`.
+    Debug(V),
+    /// Message emitted by `stderr`.
+    Stderr(V),
+}
+
+/// Callback that receives messages.
+pub type Sink<'a, V> = &'a dyn Fn(Event<V>);
+
+/// Global data that receives messages emitted by filters.
+pub trait HasSink<V> {
+    /// Handle a message.
+    fn emit(&self, event: Event<V>);
+}
+
+impl<V> HasSink<V> for Sink<'_, V> {
+    fn emit(&self, event: Event<V>) {
+        self(event)
+    }
+}
+
+/// The `debug` and `stderr` filters, reporting to [`HasSink`].
+pub fn funs<D: DataT>() -> Box<[Filter<RunPtr<D>>]>
+where
+    for<'a> D::Data<'a>: HasSink<D::V<'a>>,
+{
+    Box::new([
+        ("debug_empty", v(0), |cv| {
+            cv.0.data().emit(Event::Debug(cv.1));
+            Box::new(core::iter::empty())
+        }),
+        ("stderr_empty", v(0), |cv| {
+            cv.0.data().emit(Event::Stderr(cv.1));
+            Box::new(core::iter::empty())
+        }),
+    ])
+}
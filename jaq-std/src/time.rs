@@ -1,12 +1,14 @@
 use crate::{Error, ValR, ValT, ValTx};
 use alloc::string::{String, ToString};
-use jiff::{civil::DateTime, fmt::strtime, tz, Timestamp};
+use jiff::{civil::DateTime, fmt::strtime, tz, Span, Timestamp};
 
 /// Convert a UNIX epoch timestamp with optional fractions.
+///
+/// Fractions are rounded to the nearest microsecond.
 fn epoch_to_timestamp<V: ValT>(v: &V) -> Result<Timestamp, Error<V>> {
     let val = match v.as_isize() {
         Some(i) => i as i64 * 1000000,
-        None => (v.try_as_f64()? * 1000000.0) as i64,
+        None => (v.try_as_f64()? * 1000000.0).round() as i64,
     };
     Timestamp::from_microsecond(val).map_err(Error::str)
 }
@@ -25,7 +27,8 @@ fn timestamp_to_epoch<V: ValT>(ts: Timestamp, frac: bool) -> ValR<V> {
 
 fn array_to_datetime<V: ValT>(v: &[V]) -> Option<Result<DateTime, jiff::Error>> {
     let [year, month, day, hour, min, sec]: &[V; 6] = v.get(..6)?.try_into().ok()?;
-    let sec = sec.as_f64()?;
+    // round seconds to the nearest microsecond, like `epoch_to_timestamp`
+    let micros = (sec.as_f64()? * 1e6).round() as i64;
     let i8 = |v: &V| -> Option<i8> { v.as_isize()?.try_into().ok() };
     Some(DateTime::new(
         year.as_isize()?.try_into().ok()?,
@@ -33,9 +36,8 @@ fn array_to_datetime<V: ValT>(v: &[V]) -> Option<Result<DateTime, jiff::Error>>
         i8(day)?,
         i8(hour)?,
         i8(min)?,
-        // the `as i8` cast saturates, returning a number in the range [-128, 128]
-        sec.floor() as i8,
-        (sec.fract() * 1e9) as i32,
+        micros.div_euclid(1000000).try_into().ok()?,
+        micros.rem_euclid(1000000) as i32 * 1000,
     ))
 }
 
@@ -69,12 +71,7 @@ pub fn from_iso8601<V: ValT>(s: &str) -> ValR<V> {
 
 /// Format a number as an ISO 8601 timestamp string.
 pub fn to_iso8601<V: ValT>(v: &V) -> Result<String, Error<V>> {
-    let ts = if let Some(i) = v.as_isize() {
-        Timestamp::from_second(i as i64)
-    } else {
-        Timestamp::from_microsecond((v.try_as_f64()? * 1e6) as i64)
-    };
-    Ok(ts.map_err(Error::str)?.to_string())
+    Ok(epoch_to_timestamp(v)?.to_string())
 }
 
 /// Format a date (either number or array) in a given timezone.
@@ -84,7 +81,7 @@ pub fn to_iso8601<V: ValT>(v: &V) -> Result<String, Error<V>> {
 /// When the input is an integer, i.e. a Unix epoch,
 /// then it is *converted* to the given timezone.
 pub fn strftime<V: ValT>(v: &V, fmt: &str, tz: tz::TimeZone) -> ValR<V> {
-    let fail = || Error::str(format_args!("cannot convert {v} to time"));
+    let fail = || Error::with_val("cannot convert ", v.clone(), " to time");
     let zoned = match v.clone().into_vec() {
         Ok(v) => array_to_datetime(&v)
             .ok_or_else(fail)?
@@ -104,22 +101,45 @@ pub fn gmtime<V: ValT>(v: &V, tz: tz::TimeZone) -> ValR<V> {
 }
 
 /// Parse a string into a "broken down time" array.
+///
+/// If the string contains a time zone or an offset, the time is converted to UTC.
 pub fn strptime<V: ValT>(s: &str, fmt: &str) -> ValR<V> {
     let mut bdt = strtime::BrokenDownTime::parse(fmt, s).map_err(Error::str)?;
     if (bdt.offset(), bdt.iana_time_zone()) == (None, None) {
         bdt.set_offset(Some(tz::Offset::UTC));
     }
-    let dt = bdt.to_zoned().map_err(Error::str)?.into();
+    let zoned = bdt.to_zoned().map_err(Error::str)?;
+    let dt = zoned.with_time_zone(tz::TimeZone::UTC).into();
     datetime_to_array(dt).into_iter().map(Ok).collect()
 }
 
 /// Parse an array into a UNIX epoch timestamp.
 pub fn mktime<V: ValT>(v: &V) -> ValR<V> {
-    let fail = || Error::str(format_args!("cannot convert {v} to time"));
+    let fail = || Error::with_val("cannot convert ", v.clone(), " to time");
     let ts = array_to_datetime(&v.clone().into_vec()?)
         .ok_or_else(fail)?
         .and_then(|dt| dt.to_zoned(tz::TimeZone::UTC))
         .map_err(Error::str)?
         .timestamp();
-    timestamp_to_epoch(ts, ts.subsec_nanosecond() > 0)
+    timestamp_to_epoch(ts, ts.subsec_nanosecond() != 0)
+}
+
+/// Add `n` units of time to a UNIX epoch timestamp.
+///
+/// When adding months or years yields a day that does not exist in the target month,
+/// the day is clamped to the last day of that month.
+pub fn dateadd<V: ValT>(v: &V, unit: &str, n: &V) -> ValR<V> {
+    let n = n.try_as_isize()? as i64;
+    let span = match unit {
+        "seconds" => Span::new().try_seconds(n),
+        "minutes" => Span::new().try_minutes(n),
+        "hours" => Span::new().try_hours(n),
+        "days" => Span::new().try_days(n),
+        "months" => Span::new().try_months(n),
+        "years" => Span::new().try_years(n),
+        _ => return Err(Error::str(format_args!("unknown time unit: {unit}"))),
+    };
+    let zoned = epoch_to_timestamp(v)?.to_zoned(tz::TimeZone::UTC);
+    let ts = span.and_then(|span| zoned.checked_add(span));
+    timestamp_to_epoch(ts.map_err(Error::str)?.timestamp(), v.as_isize().is_none())
 }
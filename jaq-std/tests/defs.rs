@@ -9,12 +9,33 @@ use serde_json::json;
 fn add() {
     give(json!({"a": 1, "b": 2}), "add", json!(3));
     give(json!([[0, 1], [2, 3]]), "add", json!([0, 1, 2, 3]));
+    give(json!(["a", "b"]), "add", json!("ab"));
+    give(json!([{"a": 1}, {"b": 2}]), "add", json!({"a": 1, "b": 2}));
+    give(json!([]), "add", json!(null));
 }
 
+// `add` fails exactly like `+` does on incompatible values
+yields!(
+    add_mixed,
+    r#"[try ([1, "a"] | add) catch .] == [try (1 + "a") catch .]"#,
+    true
+);
+
 // aliases for fromdateiso8601 and todateiso8601
 yields!(fromdate, r#""1970-01-02T00:00:00Z" | fromdate"#, 86400);
 yields!(todate, r#"86400 | todate"#, "1970-01-02T00:00:00Z");
 yields!(tofromdate, "946684800 | todate | fromdate", 946684800);
+yields!(date, r#"86400 | date"#, "1970-01-02T00:00:00Z");
+yields!(datesub, r#"0 | datesub("minutes"; 2)"#, -120);
+
+// `debug(msgs)` logs its messages, but yields its input exactly once
+yields!(
+    debug_msg,
+    r#"[1, 2] | map(debug("processing \(.)"))"#,
+    [1, 2]
+);
+yields!(debug_msgs, "[0 | debug(1, 2)]", [0]);
+yields!(debug_msgs_empty, "[0 | debug(empty)]", [0]);
 
 yields!(
     drem_nan,
@@ -47,6 +68,99 @@ yields!(
 yields!(flatten_obj, "{a: 1} | flatten", json!([{"a": 1}]));
 // jq gives an error here
 yields!(flatten_num, "0 | flatten", [0]);
+yields!(
+    flatten_neg,
+    "try ([[0]] | flatten(-1)) catch .",
+    "flatten depth must not be negative"
+);
+yields!(
+    flatten_frac,
+    "[[[0, [1, [2]]]] | flatten(1.5), flatten(0.5)]",
+    json!([[0, 1, [2]], [0, [1, [2]]]])
+);
+
+yields!(splitn_0, r#""a,b,c" | splitn(","; 0)"#, ["a,b,c"]);
+yields!(splitn_1, r#""a,,b" | splitn(","; 1)"#, ["a", ",b"]);
+yields!(splitn_big, r#""a,b" | splitn(","; 5)"#, ["a", "b"]);
+yields!(
+    splitn_neg,
+    r#""a,b" | try splitn(","; -1) catch ."#,
+    "splitn count must not be negative"
+);
+// the separator is not a regex
+yields!(splitn_dot, r#""a.b" | splitn("."; 1)"#, ["a", "b"]);
+yields!(splitn_empty_sep, r#""aö😀" | splitn(""; 1)"#, ["a", "ö😀"]);
+yields!(splitn_empty, r#""" | splitn(","; 1)"#, json!([]));
+// jq's `split($re; $flags)` is unaffected
+yields!(
+    split_re_flags,
+    r#""a1b2c" | split("[0-9]"; null)"#,
+    ["a", "b", "c"]
+);
+yields!(split_empty_sep, r#""aö😀" | split("")"#, ["a", "ö", "😀"]);
+yields!(split_empty_both, r#""" | split("") == []"#, true);
+
+yields!(
+    leaf_paths,
+    // like in jq, `null` and `false` leaves are omitted
+    "[{a: [1, {}], b: null, c: false, d: \"\"} | leaf_paths]",
+    json!([["a", 0], ["d"]])
+);
+// paths are yielded depth-first, objects in insertion order, arrays by index
+yields!(
+    paths_order,
+    r#"{"b": {"z": 1, "a": [2, 3]}, "a": 0} | [paths]"#,
+    json!([
+        ["b"],
+        ["b", "z"],
+        ["b", "a"],
+        ["b", "a", 0],
+        ["b", "a", 1],
+        ["a"]
+    ])
+);
+yields!(
+    paths_order_leaf,
+    r#"{"z": [{"y": 1, "x": 2}], "a": 3} | [leaf_paths]"#,
+    json!([["z", 0, "y"], ["z", 0, "x"], ["a"]])
+);
+yields!(
+    paths_order_value,
+    r#"{"z": 1, "a": [2]} | [path_value(..)]"#,
+    json!([[[], {"z": 1, "a": [2]}], [["z"], 1], [["a"], [2]], [["a", 0], 2]])
+);
+yields!(
+    pick_obj,
+    "{a: {b: 1, c: 2}, d: 3} | pick(.a.c, .d)",
+    json!({"a": {"c": 2}, "d": 3})
+);
+// `paths(p)` must not evaluate `p` on values after the first match
+yields!(
+    paths_lazy,
+    r#"[1, "a"] | first(paths(if isstring then error else . == 1 end))"#,
+    [0]
+);
+
+yields!(in_some, "2 | IN(1, 2, 3)", true);
+yields!(in_none, "[IN(1, 2; 3, 4)]", [false]);
+// `IN` stops at the first match
+yields!(in_short, "IN(repeat(1); 0, 1, error)", true);
+yields!(
+    index_expr,
+    r#"[{"id": 1, "v": "a"}, {"id": 2, "v": "b"}, {"id": 1, "v": "c"}] | INDEX(.id)"#,
+    json!({"1": {"id": 1, "v": "c"}, "2": {"id": 2, "v": "b"}})
+);
+yields!(
+    index_src,
+    "INDEX(range(3); . * 2)",
+    json!({"0": 0, "2": 1, "4": 2})
+);
+yields!(
+    group_by_sql,
+    "[1, 2, 3, 4] | GROUP_BY(. % 2)",
+    [[2, 4], [1, 3]]
+);
+yields!(unique_by_sql, "[1, 2, 3, 4] | UNIQUE_BY(. % 2)", [2, 1]);
 
 yields!(isfinite_true, "all((0, 1, nan); isfinite)", true);
 yields!(
@@ -98,6 +212,7 @@ fn min_max() {
 }
 
 yields!(min_empty, "[] | min_by(.)", json!(null));
+yields!(min_max_empty, "[] | [min, max]", json!([null, null]));
 // when output is equal, min_by selects the left element and max_by the right one
 yields!(
     min_max_eq,
@@ -257,6 +372,13 @@ yields!(
     r#"{a: "b"} | try @sh catch -1"#,
     -1
 );
+// every array element is quoted fully, so it stays a single shell argument
+yields!(
+    format_sh_array_quoted,
+    r#"["a b", "$HOME", "", "it's"] | @sh"#,
+    r#"'a b' '$HOME' '' 'it'\''s'"#
+);
+yields!(format_sh_array_empty, "[] | @sh", "");
 yields!(
     format_sh_rejects_nested_arrays,
     r#"["fine, but", []] | try @sh catch -1"#,
@@ -279,3 +401,38 @@ yields!(
     r#"["abc%F0%9F%91%8D%20%2B%26%3F%2F", "", null, 123, [], {} | try @urid catch .]"#,
     ["abc👍 +&?/", "", "null", "123", "[]", "{}"]
 );
+
+// internal helpers are not listed
+yields!(
+    builtins_internal,
+    r#"builtins | map(select(. == "splits_/2" or . == "max_or_empty/0"))"#,
+    json!([])
+);
+// filters commonly used in jq programs should all be available
+yields!(
+    builtins_jq_staples,
+    r#"[
+        "map/1", "map_values/1", "select/1",
+        "recurse/0", "recurse/1", "recurse/2", "recurse_down/0",
+        "values/0", "nulls/0", "booleans/0", "numbers/0", "strings/0",
+        "arrays/0", "objects/0", "iterables/0", "scalars/0"
+    ] - builtins"#,
+    json!([])
+);
+yields!(
+    recurse_down,
+    "[[1, [2]] | recurse_down] == [[1, [2]] | recurse]",
+    true
+);
+
+yields!(
+    downcase_keys_nested,
+    r#"{"ID": 1, "Tags": [{"Name": "X"}], "s": "AbC"} | downcase_keys"#,
+    json!({"id": 1, "tags": [{"name": "X"}], "s": "AbC"})
+);
+// on collision, the last value wins, at the position of the first key
+yields!(
+    downcase_keys_collision,
+    r#"{"A": 1, "b": 2, "a": 3, "B": 4} | downcase_keys | [keys_unsorted, [.[]]]"#,
+    json!([["a", "b"], [3, 4]])
+);
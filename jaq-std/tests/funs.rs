@@ -11,6 +11,29 @@ fn ascii() {
     give(json!("aAaAäの"), "ascii_downcase", json!("aaaaäの"));
 }
 
+#[test]
+fn ascii_codepoints() {
+    let cps = json!([97, 65, 228, -200, 1.5, "a"]);
+    give(
+        cps.clone(),
+        "ascii_upcase",
+        json!([65, 65, 228, -200, 1.5, "a"]),
+    );
+    give(cps, "ascii_downcase", json!([97, 97, 228, -200, 1.5, "a"]));
+}
+
+#[test]
+fn case() {
+    give(json!("aAaAäの"), "upcase", json!("AAAAÄの"));
+    give(json!("aAaAÄの"), "downcase", json!("aaaaäの"));
+    // case conversion may change the length of strings
+    give(json!("ß"), "upcase", json!("SS"));
+    give(json!("İ"), "downcase", json!("i\u{307}"));
+}
+
+yields!(ascii_char, "[65, 9728] | map(ascii) | add", "A☀");
+yields!(ascii_invalid, "1114112 | try ascii catch -1", -1);
+
 yields!(
     fromdate,
     r#""1970-01-02T00:00:00Z" | fromdateiso8601"#,
@@ -42,6 +65,28 @@ yields!(
     r#"86400.123456 | strftime("%F %T.%6f")"#,
     "1970-01-02 00:00:00.123456"
 );
+yields!(
+    strftime_gmtime_mu,
+    r#"86400.123456 | gmtime | strftime("%F %T.%6f")"#,
+    "1970-01-02 00:00:00.123456"
+);
+yields!(
+    env_var,
+    "env as $e | all($e | keys[]; env(.) == $e[.])",
+    true
+);
+yields!(
+    env_var_missing,
+    r#"env("JAQ_SURELY_UNSET_VARIABLE")"#,
+    json!(null)
+);
+yields!(env_var_invalid, "try env(1) catch -1", -1);
+
+yields!(
+    strftime_gmtime_now,
+    r#"now | (gmtime | strftime("%s")) == (floor | tostring)"#,
+    true
+);
 yields!(gmtime, r"86400 | gmtime", [1970, 0, 2, 0, 0, 0, 5, 1]);
 yields!(
     gmtime_mu,
@@ -58,7 +103,64 @@ yields!(
     r#""1970-01-02T00:00:00Z" | strptime("%Y-%m-%dT%H:%M:%SZ")"#,
     [1970, 0, 2, 0, 0, 0, 5, 1]
 );
+yields!(
+    strptime_offset,
+    r#""2015-03-01T01:00:00+0200" | strptime("%Y-%m-%dT%H:%M:%S%z")"#,
+    [2015, 1, 28, 23, 0, 0, 6, 58]
+);
+yields!(
+    strptime_offset_mktime,
+    r#""2015-03-05T23:51:47+0200" | strptime("%Y-%m-%dT%H:%M:%S%z") | mktime"#,
+    1425592307
+);
 yields!(mktime, "[ 1970, 0, 2, 0, 0, 0, 5, 1 ] | mktime", 86400);
+// fractional seconds are preserved up to microseconds
+yields!(
+    gmtime_mktime_frac,
+    "[1.001, 1700000000.123, -0.5, -1.25] | . == map(gmtime | mktime)",
+    true
+);
+yields!(todate_frac, "1.001 | todate", "1970-01-01T00:00:01.001Z");
+
+yields!(
+    dateadd,
+    r#"[0 | dateadd("seconds", "minutes", "hours", "days"; 2)]"#,
+    [2, 120, 7200, 172800]
+);
+yields!(dateadd_neg, r#"86400 | dateadd("days"; -1)"#, 0);
+
+const DATEADD: &str = r#"def f($u; $n): fromdate | dateadd($u; $n) | todate;"#;
+yields!(
+    dateadd_month_clamp,
+    &(DATEADD.to_owned() + r#""2023-01-31T12:00:00Z" | f("months"; 1)"#),
+    "2023-02-28T12:00:00Z"
+);
+yields!(
+    dateadd_month_leap,
+    &(DATEADD.to_owned() + r#""2024-01-31T12:00:00Z" | f("months"; 1)"#),
+    "2024-02-29T12:00:00Z"
+);
+yields!(
+    dateadd_month_overflow,
+    &(DATEADD.to_owned() + r#""2023-12-15T00:00:00Z" | f("months"; 13)"#),
+    "2025-01-15T00:00:00Z"
+);
+yields!(
+    dateadd_month_neg,
+    &(DATEADD.to_owned() + r#""2024-03-31T00:00:00Z" | f("months"; -1)"#),
+    "2024-02-29T00:00:00Z"
+);
+yields!(
+    dateadd_year_leap,
+    &(DATEADD.to_owned() + r#""2024-02-29T00:00:00Z" | [f("years"; 1, 4)]"#),
+    ["2025-02-28T00:00:00Z", "2028-02-29T00:00:00Z"]
+);
+yields!(dateadd_frac, r#"0.5 | dateadd("seconds"; 1)"#, 1.5);
+yields!(
+    dateadd_unit,
+    r#"0 | try dateadd("fortnights"; 1) catch ."#,
+    "unknown time unit: fortnights"
+);
 
 #[test]
 fn fromtodate() {
@@ -79,6 +181,17 @@ fn explode_implode() {
 }
 
 yields!(implode_invalid, "[1114112] | try implode catch -1", -1);
+yields!(implode_surrogate, "[55296] | try implode catch -1", -1);
+yields!(
+    implode_invalid_index,
+    "[65, 1114112, 66] | try implode catch .",
+    "cannot use 1114112 at index 1 as character"
+);
+yields!(
+    implode_non_int_index,
+    "[65, \"a\"] | try implode catch .",
+    "cannot use \"a\" at index 1 as character"
+);
 
 yields!(
     encode_base64,
@@ -91,6 +204,59 @@ yields!(
     "hello cruel world"
 );
 
+yields!(
+    decode_base64_lenient_padding,
+    r#""aGVsbG8gY3J1ZWwgd29ybGQ" | decode_base64_lenient"#,
+    "hello cruel world"
+);
+yields!(
+    decode_base64_lenient_urlsafe,
+    r#"["Pz4_fn5-", "Pz4/fn5+"] | map(decode_base64_lenient)"#,
+    ["?>?~~~", "?>?~~~"]
+);
+yields!(
+    decode_base64_strict,
+    r#"["aGVsbG8gY3J1ZWwgd29ybGQ", "Pz4_fn5-"] | map(try decode_base64 catch 0)"#,
+    [0, 0]
+);
+yields!(
+    decode_base64_lenient_invalid,
+    r#"["a", "a===", "Pz4.", "Pz4/fn5+="] | map(try decode_base64_lenient catch 0)"#,
+    [0, 0, 0, 0]
+);
+// round-trip strings of different lengths, with and without padding
+yields!(
+    decode_base64_lenient_roundtrip,
+    r#"all(range(40) | [range(.) | . * 37 % 128] | implode;
+      . as $s | encode_base64 | (., rtrimstr_all("=")) | decode_base64_lenient == $s)"#,
+    true
+);
+
+#[test]
+fn base32() {
+    // test vectors from RFC 4648
+    let pairs = [
+        ("", ""),
+        ("f", "MY======"),
+        ("fo", "MZXQ===="),
+        ("foo", "MZXW6==="),
+        ("foob", "MZXW6YQ="),
+        ("fooba", "MZXW6YTB"),
+        ("foobar", "MZXW6YTBOI======"),
+    ];
+    for (dec, enc) in pairs {
+        give(json!(dec), "encode_base32", json!(enc));
+        give(json!(enc), "decode_base32", json!(dec));
+    }
+}
+
+// padding in the middle, wrong length, invalid character, invalid padding
+yields!(
+    decode_base32_invalid,
+    r#"["MY======MY======", "MY=====", "M1======", "M======="] | map(try decode_base32 catch 0)"#,
+    [0, 0, 0, 0]
+);
+
 yields!(
     escape_html,
     r#""<p style='visibility: hidden'>sneaky</p>" | escape_html"#,
@@ -101,6 +267,16 @@ yields!(
     r#""abc123 ?#+&[]" | encode_uri"#,
     "abc123%20%3F%23%2B%26%5B%5D"
 );
+yields!(
+    encode_uri_full,
+    r#""https://example.com/a b/ü?x=1&y=[2]#f~%" | encode_uri_full"#,
+    "https://example.com/a%20b/%C3%BC?x=1&y=[2]#f~%25"
+);
+yields!(
+    encode_uri_full_decode,
+    r#""/a b/ü?x=%" | encode_uri_full | decode_uri"#,
+    "/a b/ü?x=%"
+);
 
 #[test]
 fn group_by() {
@@ -112,6 +288,15 @@ fn group_by() {
     );
 }
 
+yields!(unique, "[3, 2, 1, 3, 4, 1] | unique", [1, 2, 3, 4]);
+// on ties, the element that comes first in the input is kept
+yields!(unique_by_first, "[3, 1, 2, 4] | unique_by(. % 2)", [2, 3]);
+
+yields!(ltrimarr, "[1, 2, 1, 2] | ltrimarr([1, 2])", [1, 2]);
+yields!(rtrimarr, "[1, 2, 3, 4] | rtrimarr([3, 4])", [1, 2]);
+yields!(rtrimarr_none, "[1, 2, 3] | rtrimarr([2])", [1, 2, 3]);
+yields!(ltrimarr_all, "[1, 2] | ltrimarr([1, 2]) == []", true);
+
 yields!(utf8bytelength_foo1, r#""foo" | utf8bytelength"#, 3);
 yields!(utf8bytelength_foo2, r#""ƒoo" | utf8bytelength"#, 4);
 yields!(utf8bytelength_namaste, r#""नमस्ते" | utf8bytelength"#, 18);
@@ -179,6 +364,141 @@ fn regex() {
     give(json!(s), &f("split_matches", date, ""), out);
 }
 
+yields!(
+    regex_flags_i,
+    r#""aB" | [test("ab"), test("ab"; "i")]"#,
+    [false, true]
+);
+yields!(
+    regex_flags_ms,
+    r#""a\nb" | [test("^b$"), test("^b$"; "m"), test("a.b"), test("a.b"; "s")]"#,
+    [false, true, false, true]
+);
+// `p` is equivalent to `ms`
+yields!(
+    regex_flags_p,
+    r#""a\nb" | [test("^b$"; "p"), test("a.b"; "p")]"#,
+    [true, true]
+);
+yields!(
+    regex_flags_x,
+    r#""ab" | [test("a b # comment"), test("a b # comment"; "x")]"#,
+    [false, true]
+);
+yields!(
+    regex_flags_l,
+    r#""aaa" | [match("a+"; "", "l") | .string]"#,
+    ["aaa", "a"]
+);
+yields!(
+    regex_flags_gn,
+    r#""a,,b" | [[scan("[a-z]*"; "g")], [scan("[a-z]*"; "gn")]]"#,
+    json!([["a", "", "b"], ["a", "b"]])
+);
+yields!(
+    regex_flags_invalid,
+    r#""a" | try test("a"; "gq") catch ."#,
+    "invalid regex flag: q"
+);
+yields!(
+    regex_size_limit,
+    r#"try test("a{1000}{1000}") catch startswith("invalid regex")"#,
+    true
+);
+yields!(
+    regex_nest_limit,
+    r#"try test("(" * 100 + "a" + ")" * 100) catch startswith("invalid regex")"#,
+    true
+);
+// the regex engine does not backtrack, so this terminates quickly
+yields!(
+    regex_no_backtracking,
+    r#""a" * 10000 | [test("(a*)*b"), test("(a|aa)+$")]"#,
+    [false, true]
+);
+
+yields!(splits_first, r#"first("a, b, c" | splits(", *"))"#, "a");
+yields!(
+    splits_limit,
+    r#"["a1b2c3" | limit(2; splits("\\d"))]"#,
+    ["a", "b"]
+);
+yields!(
+    splits_trailing,
+    r#"["a1b2" | splits("\\d")] == ("a1b2" | split("\\d"; null))"#,
+    true
+);
+yields!(splits_empty, r#"["ab" | splits("")]"#, ["", "a", "b", ""]);
+yields!(splits_n, r#"["ab" | splits(""; "n")]"#, ["ab"]);
+
+yields!(
+    tonumber_str,
+    r#"["1", "-0.5", "1e3", "NaN" | tonumber | isnan]"#,
+    [false, false, false, true]
+);
+yields!(
+    tonumber_invalid,
+    r#"[" 1", "01", "1 2", "0x10", "" | try tonumber catch "fail"]"#,
+    ["fail", "fail", "fail", "fail", "fail"]
+);
+yields!(
+    tonumber_err,
+    r#"try ("abc" | tonumber) catch ."#,
+    "cannot parse \"abc\" as number"
+);
+yields!(
+    tonumber_err_long,
+    r#"try ("x" * 1000 | tonumber) catch ."#,
+    "cannot parse \"xxxxxxxxxxxxxxxxxxxxxxxxxxxxx... as number"
+);
+yields!(
+    tostring_num,
+    "[1, 1.5, null] | map(tostring)",
+    ["1", "1.5", "null"]
+);
+
+yields!(
+    isnan_nonnum,
+    r#"[null, "nan", [] | isnan, isinfinite]"#,
+    [false, false, false, false, false, false]
+);
+yields!(
+    isnormal_subnormal,
+    "[1e-310, 1e-300 | isnormal]",
+    [false, true]
+);
+yields!(
+    isinfinite_bigint,
+    "[100000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000 | isinfinite, isnormal]",
+    [false, true]
+);
+
+yields!(abs_int, "[-1, 0, 1 | abs]", [1, 0, 1]);
+yields!(
+    abs_bigint,
+    "-100000000000000000000000000000 | abs | . == 100000000000000000000000000000",
+    true
+);
+yields!(abs_nonnum, r#"["a", [], {} | abs] == ["a", [], {}]"#, true);
+yields!(
+    signum,
+    "[-2.5, -1, 0, 0.0, 1, 3.5 | signum]",
+    [-1, -1, 0, 0, 1, 1]
+);
+yields!(signum_nan, "nan | signum | isnan", true);
+yields!(
+    signum_str,
+    r#"try ("a" | signum) catch ."#,
+    "cannot use \"a\" as number"
+);
+
+yields!(toarray_arr, "[1, 2] | toarray", [1, 2]);
+yields!(
+    toarray_other,
+    r#"[null, 1, "a", {} | toarray] == [[null], [1], ["a"], [{}]]"#,
+    true
+);
+
 yields!(round_int, "[0, 1][1 | round]", 1);
 
 yields!(round_pi, " 1   | round", 1);
@@ -213,6 +533,22 @@ yields!(
     [1, 2]
 );
 
+yields!(
+    sort_by_ci,
+    r#"["b", "B", "a", "C", "Ä", "ä", 1] | sort_by_ci(.)"#,
+    json!([1, "a", "b", "B", "C", "Ä", "ä"])
+);
+yields!(
+    sort_by_ci_key,
+    r#"[{"n": "bob"}, {"n": "Alice"}, {"n": "alice"}] | sort_by_ci(.n) | map(.n)"#,
+    ["Alice", "alice", "bob"]
+);
+yields!(
+    sort_by_ci_err,
+    r#"[1, 2] | try sort_by_ci(error) catch "caught""#,
+    "caught"
+);
+
 #[test]
 fn startswith() {
     give(json!("foobar"), r#"startswith("")"#, json!(true));
@@ -229,6 +565,43 @@ fn endswith() {
     give(json!(""), r#"endswith("foo")"#, json!(false));
 }
 
+yields!(
+    startswith_arr,
+    "[1, 2, 3] | [startswith([]), startswith([1, 2]), startswith([2])]",
+    [true, true, false]
+);
+yields!(
+    endswith_arr,
+    "[1, 2, 3] | [endswith([2, 3]), endswith([1, 2, 3, 4])]",
+    [true, false]
+);
+yields!(
+    startswith_arr_str,
+    "[1, 2] | try startswith(\"1\") catch 0",
+    0
+);
+yields!(
+    startswith_str_arr,
+    "\"ab\" | try startswith([\"a\"]) catch 0",
+    0
+);
+
+#[test]
+fn startswith_ci() {
+    give(json!("FooBar"), r#"startswith_ci("fOO")"#, json!(true));
+    give(json!("FooBar"), r#"startswith_ci("bar")"#, json!(false));
+    give(json!("Fo"), r#"startswith_ci("foo")"#, json!(false));
+    // only ASCII letters are folded
+    give(json!("Über"), r#"startswith_ci("über")"#, json!(false));
+}
+
+#[test]
+fn endswith_ci() {
+    give(json!("FooBar"), r#"endswith_ci("BAR")"#, json!(true));
+    give(json!("FooBar"), r#"endswith_ci("foo")"#, json!(false));
+    give(json!("ar"), r#"endswith_ci("bar")"#, json!(false));
+}
+
 #[test]
 fn ltrimstr() {
     give(json!("foobar"), r#"ltrimstr("")"#, json!("foobar"));
@@ -245,6 +618,21 @@ fn rtrimstr() {
     give(json!("اَلْعَرَبِيَّةُ"), r#"rtrimstr("ا")"#, json!("اَلْعَرَبِيَّةُ"));
 }
 
+#[test]
+fn trimstr_all() {
+    give(json!("abababc"), r#"ltrimstr_all("ab")"#, json!("c"));
+    give(json!("cababab"), r#"rtrimstr_all("ab")"#, json!("c"));
+    give(json!("abcab"), r#"ltrimstr_all("")"#, json!("abcab"));
+    give(json!("ababcabab"), r#"trimstr_all("ab")"#, json!("c"));
+    give(json!("ababcabab"), r#"trimstr("ab")"#, json!("abcab"));
+}
+
+yields!(
+    trimstr_all_num,
+    r#"try (0 | ltrimstr_all("a")) catch -1"#,
+    -1
+);
+
 #[test]
 fn trim() {
     give(json!(""), "trim", json!(""));
@@ -23,6 +23,7 @@ pub struct Cli {
     pub join_output: bool,
     pub in_place: bool,
     pub sort_keys: bool,
+    pub ascii_output: bool,
     pub color_output: bool,
     pub monochrome_output: bool,
     pub tab: bool,
@@ -101,6 +102,7 @@ impl Cli {
             "join-output" => self.short('j', args)?,
             "in-place" => self.short('i', args)?,
             "sort-keys" => self.short('S', args)?,
+            "ascii-output" => self.short('a', args)?,
             "color-output" => self.short('C', args)?,
             "monochrome-output" => self.short('M', args)?,
             "tab" => self.tab = true,
@@ -146,6 +148,7 @@ impl Cli {
             }
             'i' => self.in_place = true,
             'S' => self.sort_keys = true,
+            'a' => self.ascii_output = true,
             'C' => self.color_output = true,
             'M' => self.monochrome_output = true,
 
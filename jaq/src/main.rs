@@ -90,6 +90,8 @@ impl Cli {
             sort_keys: self.sort_keys,
             styles: self.styles(),
             sep_space: !self.compact_output || matches!(self.to, Some(Format::Yaml)),
+            float_prec: None,
+            ascii: self.ascii_output,
         }
     }
 
@@ -122,6 +122,14 @@ test!(
 {"inputs":[0,1,2,3]}"#
 );
 
+test!(
+    input_exhausted,
+    &["-nc", r#"[inputs], (try input catch .)"#],
+    "0\n1",
+    r#"[0,1]
+"no more inputs""#
+);
+
 const ONE23: &str = "One\nTwo\nThree\n";
 
 test!(raw_input_slurp, &["-Rs"], ONE23, r#""One\nTwo\nThree\n""#);